@@ -0,0 +1,169 @@
+//! Sequential vs parallel scanning and flow emission on a synthetic thousand-card tree.
+//!
+//! Run with `cargo bench --bench parallel [-- --jobs N]`. Emission uses a stub `greentic-flow`
+//! script, so the numbers measure process fan-out rather than the real CLI.
+
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use greentic_cards2pack::emit_flow::emit_flow;
+use greentic_cards2pack::graph::build_flow_graph;
+use greentic_cards2pack::parallel::{map_ordered, worker_count};
+use greentic_cards2pack::scan::{ScanConfig, scan_cards};
+use serde_json::json;
+use tempfile::TempDir;
+
+const FLOWS: usize = 20;
+const CARDS_PER_FLOW: usize = 50;
+const SCAN_ROUNDS: usize = 5;
+
+fn main() {
+    let tmp = TempDir::new().expect("temp dir");
+    let cards_dir = tmp.path().join("cards");
+    write_cards(&cards_dir);
+
+    let args: Vec<String> = std::env::args().collect();
+    let requested = args
+        .iter()
+        .position(|arg| arg == "--jobs")
+        .and_then(|index| args.get(index + 1)?.parse().ok());
+    let jobs = worker_count(requested);
+    println!(
+        "synthetic tree: {} cards in {FLOWS} flows, {jobs} workers",
+        FLOWS * CARDS_PER_FLOW
+    );
+
+    let scan = |jobs: usize| {
+        let config = ScanConfig {
+            cards_dir: cards_dir.clone(),
+            jobs: Some(jobs),
+            ..ScanConfig::default()
+        };
+        let mut best = Duration::MAX;
+        let mut manifest = None;
+        for _ in 0..SCAN_ROUNDS {
+            let started = Instant::now();
+            manifest = Some(scan_cards(&config).expect("scan"));
+            best = best.min(started.elapsed());
+        }
+        (best, manifest.expect("at least one round"))
+    };
+    let (sequential, expected) = scan(1);
+    let (parallel, actual) = scan(jobs);
+    assert_eq!(
+        serde_json::to_value(&expected.flows).unwrap(),
+        serde_json::to_value(&actual.flows).unwrap(),
+        "parallel scan must match the sequential one"
+    );
+    report("scan", sequential, parallel);
+
+    if !install_stub_greentic_flow(tmp.path()) {
+        println!("emit: skipped (the stub greentic-flow needs a Unix shell)");
+        return;
+    }
+    let graphs: Vec<_> = expected
+        .flows
+        .iter()
+        .map(|flow| build_flow_graph(flow, false).expect("graph"))
+        .collect();
+    let emit = |jobs: usize| {
+        let out = tmp.path().join(format!("out-{jobs}"));
+        let started = Instant::now();
+        for result in map_ordered(&graphs, jobs, |graph| emit_flow(graph, &out, false)) {
+            result.expect("emit");
+        }
+        (started.elapsed(), out)
+    };
+    let (sequential, sequential_out) = emit(1);
+    let (parallel, parallel_out) = emit(jobs);
+    for graph in &graphs {
+        let rel = format!("flows/{}.ygtc", graph.flow_name);
+        assert_eq!(
+            fs::read_to_string(sequential_out.join(&rel)).unwrap(),
+            fs::read_to_string(parallel_out.join(&rel)).unwrap(),
+            "parallel emission must match the sequential one"
+        );
+    }
+    report("emit", sequential, parallel);
+}
+
+fn report(stage: &str, sequential: Duration, parallel: Duration) {
+    println!(
+        "{stage}: jobs=1 {:.1} ms, parallel {:.1} ms ({:.1}x)",
+        sequential.as_secs_f64() * 1000.0,
+        parallel.as_secs_f64() * 1000.0,
+        sequential.as_secs_f64() / parallel.as_secs_f64()
+    );
+}
+
+/// Each flow is a chain of cards with a text input and a submit action to the next card.
+fn write_cards(cards_dir: &Path) {
+    for flow in 0..FLOWS {
+        let dir = cards_dir.join(format!("flow{flow:02}"));
+        fs::create_dir_all(&dir).expect("create flow dir");
+        for card in 0..CARDS_PER_FLOW {
+            let next = (card + 1) % CARDS_PER_FLOW;
+            let value = json!({
+                "type": "AdaptiveCard",
+                "version": "1.5",
+                "greentic": { "cardId": format!("card{card:03}"), "flow": format!("flow{flow:02}") },
+                "body": [
+                    { "type": "TextBlock", "text": format!("Card {card} of flow {flow}"), "wrap": true },
+                    { "type": "Input.Text", "id": "comment", "label": "Comment", "maxLength": 200 }
+                ],
+                "actions": [
+                    { "type": "Action.Submit", "title": "Next", "data": { "step": format!("card{next:03}") } }
+                ]
+            });
+            fs::write(
+                dir.join(format!("card{card:03}.json")),
+                serde_json::to_string_pretty(&value).unwrap(),
+            )
+            .expect("write card");
+        }
+    }
+}
+
+#[cfg(unix)]
+fn install_stub_greentic_flow(root: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    let bin = root.join("bin");
+    fs::create_dir_all(&bin).expect("create bin dir");
+    let script = bin.join("greentic-flow");
+    fs::write(
+        &script,
+        r#"#!/bin/sh
+cmd="$1"
+shift
+flow=""
+node=""
+while [ $# -gt 0 ]; do
+  case "$1" in
+    --flow) flow="$2"; shift 2 ;;
+    --node-id) node="$2"; shift 2 ;;
+    *) shift ;;
+  esac
+done
+case "$cmd" in
+  new) printf 'nodes:\n' > "$flow" ;;
+  add-step) printf '  %s: {}\n' "$node" >> "$flow" ;;
+esac
+"#,
+    )
+    .expect("write stub");
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).expect("chmod stub");
+
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let mut dirs = vec![bin];
+    dirs.extend(std::env::split_paths(&path));
+    // SAFETY: the scan workers have been joined; no other thread is running.
+    unsafe { std::env::set_var("PATH", std::env::join_paths(dirs).expect("join PATH")) };
+    true
+}
+
+#[cfg(not(unix))]
+fn install_stub_greentic_flow(_root: &Path) -> bool {
+    false
+}
@@ -0,0 +1,188 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use walkdir::WalkDir;
+
+use crate::card_source::CardFormat;
+use crate::config::is_config_file;
+
+pub const IGNORE_FILE_NAME: &str = ".cards2packignore";
+
+/// Which files under `--cards` are card sources: `.cards2packignore` rules plus `--include` /
+/// `--exclude` globs. `copy_cards` and `scan_cards` both consume [`list_card_files`], so they
+/// always see the same files.
+#[derive(Debug, Clone)]
+pub struct CardFilter {
+    ignore: Gitignore,
+    include: GlobSet,
+    exclude: GlobSet,
+}
+
+impl CardFilter {
+    /// Reads `<cards_dir>/.cards2packignore` (if present) and compiles the globs.
+    pub fn load(cards_dir: &Path, include: &[String], exclude: &[String]) -> Result<Self> {
+        let ignore_path = cards_dir.join(IGNORE_FILE_NAME);
+        let mut builder = GitignoreBuilder::new(cards_dir);
+        if ignore_path.is_file()
+            && let Some(err) = builder.add(&ignore_path)
+        {
+            return Err(err).with_context(|| format!("read {}", ignore_path.display()));
+        }
+        let ignore = builder
+            .build()
+            .with_context(|| format!("parse {}", ignore_path.display()))?;
+        Ok(Self {
+            ignore,
+            include: compile_globs("--include", include)?,
+            exclude: compile_globs("--exclude", exclude)?,
+        })
+    }
+
+    /// Whether a directory (path relative to `--cards`, `/`-separated) is pruned by the ignore file.
+    fn skips_dir(&self, rel: &str) -> bool {
+        self.ignore.matched(rel, true).is_ignore()
+    }
+
+    /// Whether a file (path relative to `--cards`, `/`-separated) is a card source.
+    pub fn accepts(&self, rel: &str) -> bool {
+        if self
+            .ignore
+            .matched_path_or_any_parents(rel, false)
+            .is_ignore()
+        {
+            return false;
+        }
+        if !self.include.is_empty() && !self.include.is_match(rel) {
+            return false;
+        }
+        !self.exclude.is_match(rel)
+    }
+}
+
+/// Every card or template data source under `cards_dir` accepted by `filter`, sorted by path.
+pub fn list_card_files(cards_dir: &Path, filter: &CardFilter) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let walker = WalkDir::new(cards_dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0
+                || !entry.file_type().is_dir()
+                || !filter.skips_dir(&rel_string(cards_dir, entry.path()))
+        });
+    for entry in walker.filter_map(Result::ok) {
+        let path = entry.path();
+        if !entry.file_type().is_file()
+            || CardFormat::from_path(path).is_none()
+            || is_config_file(path)
+        {
+            continue;
+        }
+        if filter.accepts(&rel_string(cards_dir, path)) {
+            files.push(path.to_path_buf());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// `path` relative to `root`, with `/` separators on every platform.
+pub(crate) fn rel_string(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Compiles `--include` / `--exclude` globs with gitignore anchoring: a glob without an inner `/`
+/// matches at any depth, and a glob matching a directory matches every file below it.
+fn compile_globs(flag: &str, globs: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        let pattern = glob.trim().trim_end_matches('/');
+        if pattern.is_empty() || pattern.starts_with('!') || pattern.starts_with('#') {
+            bail!("invalid {flag} glob: {glob:?}");
+        }
+        let pattern = if pattern.contains('/') {
+            pattern.trim_start_matches('/').to_string()
+        } else {
+            format!("**/{pattern}")
+        };
+        for pattern in [format!("{pattern}/**"), pattern] {
+            let compiled = GlobBuilder::new(&pattern)
+                .literal_separator(true)
+                .backslash_escape(true)
+                .build()
+                .with_context(|| format!("invalid {flag} glob: {glob:?}"))?;
+            builder.add(compiled);
+        }
+    }
+    builder
+        .build()
+        .with_context(|| format!("invalid {flag} globs"))
+}
+
+#[cfg(test)]
+mod tests {
+    use ignore::gitignore::GitignoreBuilder;
+
+    use super::{CardFilter, compile_globs};
+
+    fn filter(ignore: &[&str], include: &[&str], exclude: &[&str]) -> CardFilter {
+        let mut builder = GitignoreBuilder::new("cards");
+        for line in ignore {
+            builder.add_line(None, line).unwrap();
+        }
+        let globs = |globs: &[&str]| {
+            globs
+                .iter()
+                .map(|glob| glob.to_string())
+                .collect::<Vec<_>>()
+        };
+        CardFilter {
+            ignore: builder.build().unwrap(),
+            include: compile_globs("--include", &globs(include)).unwrap(),
+            exclude: compile_globs("--exclude", &globs(exclude)).unwrap(),
+        }
+    }
+
+    #[test]
+    fn applies_gitignore_rules() {
+        let filter = filter(
+            &[
+                "# comment",
+                "drafts/",
+                "*.sample.json",
+                "/fixtures",
+                "!keep.sample.json",
+            ],
+            &[],
+            &[],
+        );
+        assert!(filter.accepts("hr/welcome.json"));
+        assert!(!filter.accepts("drafts/new.json"));
+        assert!(!filter.accepts("hr/drafts/new.json"));
+        assert!(filter.skips_dir("hr/drafts"));
+        assert!(!filter.accepts("hr/a.sample.json"));
+        assert!(filter.accepts("hr/keep.sample.json"));
+        assert!(!filter.accepts("fixtures/a.json"));
+        assert!(filter.accepts("hr/fixtures.json"));
+    }
+
+    #[test]
+    fn applies_include_and_exclude_globs() {
+        let filter = filter(
+            &[],
+            &["hr/**", "*.yaml"],
+            &["**/old-*.json", "hr/[ab]?.json"],
+        );
+        assert!(filter.accepts("hr/deep/card.json"));
+        assert!(filter.accepts("sales/card.yaml"));
+        assert!(!filter.accepts("sales/card.json"));
+        assert!(!filter.accepts("hr/x/old-card.json"));
+        assert!(!filter.accepts("hr/a1.json"));
+        assert!(filter.accepts("hr/c1.json"));
+    }
+}
@@ -0,0 +1,185 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+/// Source formats accepted under `--cards`; every card is packaged as a `.json` asset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardFormat {
+    Json,
+    Jsonc,
+    Yaml,
+}
+
+impl CardFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "json" => Some(CardFormat::Json),
+            "jsonc" => Some(CardFormat::Jsonc),
+            "yaml" | "yml" => Some(CardFormat::Yaml),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CardFormat::Json => "JSON",
+            CardFormat::Jsonc => "JSONC",
+            CardFormat::Yaml => "YAML",
+        }
+    }
+
+    pub fn parse(self, contents: &str) -> Result<Value> {
+        match self {
+            CardFormat::Json => Ok(serde_json::from_str(contents)?),
+            CardFormat::Jsonc => Ok(serde_json::from_str(&strip_jsonc(contents))?),
+            CardFormat::Yaml => Ok(serde_yaml_bw::from_str(contents)?),
+        }
+    }
+}
+
+/// Path of the normalised asset for a card source, relative to `assets/cards`.
+pub fn asset_rel_path(rel_path: &Path) -> PathBuf {
+    rel_path.with_extension("json")
+}
+
+/// Canonical JSON written for non-JSON sources.
+pub fn normalized_json(value: &Value) -> Result<String> {
+    let mut json = serde_json::to_string_pretty(value).context("serialize card")?;
+    json.push('\n');
+    Ok(json)
+}
+
+/// Blanks out `//` and `/* */` comments and trailing commas so the result parses as JSON.
+/// Removed characters become one space per byte (newlines are kept), so error positions and
+/// byte columns still match the source.
+pub fn strip_jsonc(input: &str) -> String {
+    let chars: Vec<char> = input.chars().collect();
+    let mut output: Vec<char> = Vec::with_capacity(chars.len());
+    let mut index = 0;
+    let mut in_string = false;
+
+    while index < chars.len() {
+        let current = chars[index];
+        let next = chars.get(index + 1).copied();
+        if in_string {
+            output.push(current);
+            if current == '\\' {
+                if let Some(escaped) = next {
+                    output.push(escaped);
+                    index += 1;
+                }
+            } else if current == '"' {
+                in_string = false;
+            }
+            index += 1;
+            continue;
+        }
+
+        match (current, next) {
+            ('"', _) => {
+                in_string = true;
+                output.push(current);
+                index += 1;
+            }
+            ('/', Some('/')) => {
+                while index < chars.len() && chars[index] != '\n' {
+                    blank(&mut output, chars[index]);
+                    index += 1;
+                }
+            }
+            ('/', Some('*')) => {
+                output.extend([' ', ' ']);
+                index += 2;
+                while index < chars.len()
+                    && !(chars[index] == '*' && chars.get(index + 1) == Some(&'/'))
+                {
+                    blank(&mut output, chars[index]);
+                    index += 1;
+                }
+                if index < chars.len() {
+                    output.extend([' ', ' ']);
+                    index += 2;
+                }
+            }
+            _ => {
+                output.push(current);
+                index += 1;
+            }
+        }
+    }
+
+    remove_trailing_commas(&mut output);
+    output.into_iter().collect()
+}
+
+/// Replaces `removed` with as many spaces as it has bytes, keeping newlines.
+fn blank(output: &mut Vec<char>, removed: char) {
+    if removed == '\n' {
+        output.push('\n');
+    } else {
+        output.extend(std::iter::repeat_n(' ', removed.len_utf8()));
+    }
+}
+
+fn remove_trailing_commas(chars: &mut [char]) {
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut pending_comma: Option<usize> = None;
+
+    for index in 0..chars.len() {
+        let current = chars[index];
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if current == '\\' {
+                escaped = true;
+            } else if current == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match current {
+            '"' => {
+                in_string = true;
+                pending_comma = None;
+            }
+            ',' => pending_comma = Some(index),
+            '}' | ']' => {
+                if let Some(comma) = pending_comma.take() {
+                    chars[comma] = ' ';
+                }
+            }
+            value if value.is_whitespace() => {}
+            _ => pending_comma = None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CardFormat, strip_jsonc};
+    use serde_json::json;
+
+    #[test]
+    fn strips_comments_and_trailing_commas() {
+        let source = "{\n  // note \u{e9}\n  \"url\": \"http://x/*y*/\", /* block\n  comment \u{e9} */\n  \"list\": [1, 2,],\n}\n";
+        let stripped = strip_jsonc(source);
+        assert_eq!(stripped.lines().count(), source.lines().count());
+        assert_eq!(stripped.len(), source.len());
+        let value: serde_json::Value = serde_json::from_str(&stripped).unwrap();
+        assert_eq!(value, json!({ "url": "http://x/*y*/", "list": [1, 2] }));
+    }
+
+    #[test]
+    fn parses_yaml_cards() {
+        let value = CardFormat::Yaml
+            .parse(
+                "type: AdaptiveCard\nversion: \"1.5\"\nbody:\n  - type: TextBlock\n    text: Hi\n",
+            )
+            .unwrap();
+        assert_eq!(value["body"][0]["text"], "Hi");
+        assert_eq!(value["version"], "1.5");
+    }
+}
@@ -1,7 +1,10 @@
 use std::path::PathBuf;
 
 use clap::{Args, Parser, Subcommand, ValueEnum};
-use serde::Serialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::ir::WarningKind;
 
 #[derive(Parser)]
 #[command(name = "greentic-cards2pack")]
@@ -14,47 +17,234 @@ pub struct Cli {
 #[derive(Subcommand)]
 pub enum Commands {
     Generate(GenerateArgs),
+    /// Scan cards and print the manifest without invoking greentic tooling.
+    Scan(ScanArgs),
+    /// Render each flow graph as Mermaid, Graphviz DOT or JSON.
+    Graph(GraphArgs),
 }
 
-#[derive(Args, Debug)]
+#[derive(Args, Debug, Clone)]
 pub struct GenerateArgs {
-    /// Directory of Adaptive Card JSON files.
-    #[arg(long)]
-    pub cards: PathBuf,
+    #[command(flatten)]
+    pub scan: CardScanArgs,
     /// Output workspace directory.
     #[arg(long)]
-    pub out: PathBuf,
+    pub out: Option<PathBuf>,
     /// Pack name and dist artifact name.
     #[arg(long)]
-    pub name: String,
+    pub name: Option<String>,
     /// Path to greentic-pack binary.
     #[arg(long)]
     pub greentic_pack_bin: Option<PathBuf>,
-    /// Grouping strategy for flows (stored only).
-    #[arg(long, value_enum)]
-    pub group_by: Option<GroupBy>,
-    /// Default flow name (stored only).
-    #[arg(long)]
-    pub default_flow: Option<String>,
-    /// Strict mode (stored only).
-    #[arg(long)]
-    pub strict: bool,
     /// Print greentic-pack command and output.
-    #[arg(long)]
+    #[arg(long, overrides_with = "no_verbose")]
     pub verbose: bool,
+    /// Turn off verbose output enabled in the config file.
+    #[arg(long, overrides_with = "verbose")]
+    pub no_verbose: bool,
     /// Prompt-based routing (requires prompt2flow component).
-    #[arg(long)]
+    #[arg(long, overrides_with = "no_prompt")]
     pub prompt: bool,
+    /// Turn off prompt-based routing enabled in the config file.
+    #[arg(long, overrides_with = "prompt")]
+    pub no_prompt: bool,
     /// Answers JSON produced by greentic-qa (requires --prompt).
     #[arg(long = "prompt-json")]
     pub prompt_json: Option<PathBuf>,
     /// Override prompt limits via JSON string or file (requires --prompt).
     #[arg(long = "prompt-limits")]
     pub prompt_limits: Option<String>,
+    /// Expand `${...}` templates with their `<card>.data.json` when packaging cards.
+    #[arg(long, overrides_with = "no_expand_templates")]
+    pub expand_templates: bool,
+    /// Turn off template expansion enabled in the config file.
+    #[arg(long, overrides_with = "expand_templates")]
+    pub no_expand_templates: bool,
+    /// Also write flow graphs to .cards2pack/graphs in this format.
+    #[arg(long, value_enum)]
+    pub graph: Option<GraphFormat>,
+    /// Watch --cards and regenerate on change; press Enter to build the .gtpack.
+    #[arg(long)]
+    pub watch: bool,
+    /// Quiet period in milliseconds before a watch-mode rebuild.
+    #[arg(long, default_value_t = 500)]
+    pub debounce_ms: u64,
+    /// Ignore .cards2pack/build-state.json and re-emit every flow and rebuild the pack.
+    #[arg(long)]
+    pub force: bool,
+    /// Report the files generate would create, modify or delete without touching --out.
+    #[arg(long, conflicts_with = "watch")]
+    pub dry_run: bool,
+    /// Result format; json prints diagnostics and all warnings (with --dry-run, also the planned
+    /// changes) as one document on stdout.
+    #[arg(long, value_enum, default_value = "text")]
+    pub format: OutputFormat,
+    /// Also write every warning to this file as a SARIF 2.1.0 log.
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["watch", "dry_run"])]
+    pub sarif: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct ScanArgs {
+    #[command(flatten)]
+    pub scan: CardScanArgs,
+    /// Output format.
+    #[arg(long, value_enum, default_value = "text")]
+    pub format: OutputFormat,
+    /// Also write every warning to this file as a SARIF 2.1.0 log.
+    #[arg(long, value_name = "PATH")]
+    pub sarif: Option<PathBuf>,
 }
 
-#[derive(ValueEnum, Copy, Clone, Debug, Eq, PartialEq, Serialize)]
+#[derive(Args, Debug)]
+pub struct GraphArgs {
+    #[command(flatten)]
+    pub scan: CardScanArgs,
+    /// Graph format.
+    #[arg(long, value_enum, default_value = "mermaid")]
+    pub format: GraphFormat,
+    /// Only render this flow.
+    #[arg(long)]
+    pub flow: Option<String>,
+    /// Write one file per flow into this directory instead of stdout.
+    #[arg(long)]
+    pub out: Option<PathBuf>,
+}
+
+/// Flags of every subcommand that scans cards; each one overrides the project config file.
+#[derive(Args, Debug, Clone, Default)]
+pub struct CardScanArgs {
+    /// Project config file (defaults to cards2pack.yaml/.toml next to --cards).
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+    /// Directory of Adaptive Card files.
+    #[arg(long)]
+    pub cards: Option<PathBuf>,
+    /// Group cards into flows by top-level folder (for cards naming no flow) or by --flow-field.
+    #[arg(long, value_enum)]
+    pub group_by: Option<GroupBy>,
+    /// JSON pointer read by `--group-by flow-field` (default: /greentic/flow).
+    #[arg(long)]
+    pub flow_field: Option<String>,
+    /// Flow for cards without flow metadata; generate also gives it the `default` entrypoint in
+    /// pack.yaml.
+    #[arg(long)]
+    pub default_flow: Option<String>,
+    /// Fail on invalid JSON, inconsistent metadata, mixed grouping or missing targets, after
+    /// reporting every one of them.
+    #[arg(long, overrides_with = "no_strict")]
+    pub strict: bool,
+    /// Turn off strict mode enabled in the config file.
+    #[arg(long, overrides_with = "strict")]
+    pub no_strict: bool,
+    /// Stop strict mode after this many errors (default: report all).
+    #[arg(long, value_name = "N")]
+    pub max_errors: Option<usize>,
+    #[command(flatten)]
+    pub severity: SeverityArgs,
+    #[command(flatten)]
+    pub routing_keys: RoutingKeyArgs,
+    /// Check cards against this host's schema version and element support.
+    #[arg(long, value_enum)]
+    pub host: Option<HostProfile>,
+    /// Only use card files matching this glob (relative to --cards; repeatable).
+    #[arg(long, value_name = "GLOB")]
+    pub include: Vec<String>,
+    /// Skip card files matching this glob (relative to --cards; repeatable).
+    #[arg(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+    /// Treat a language-code file name suffix as the card's locale (`welcome.de.json` is the `de`
+    /// variant of card `welcome`).
+    #[arg(long, overrides_with = "no_locale_suffixes")]
+    pub locale_suffixes: bool,
+    /// Turn off locale suffixes enabled in the config file.
+    #[arg(long, overrides_with = "locale_suffixes")]
+    pub no_locale_suffixes: bool,
+    /// Worker threads for parsing cards and, in generate, emitting flows (default: available
+    /// parallelism).
+    #[arg(long, value_name = "N")]
+    pub jobs: Option<usize>,
+}
+
+/// A `--flag`/`--no-flag` pair as an override of the config file: `None` when neither is given.
+pub fn bool_flag(on: bool, off: bool) -> Option<bool> {
+    match (on, off) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
+    }
+}
+
+/// JSON pointer overrides for routing metadata; each flag may repeat and replaces the
+/// configured list for that key.
+#[derive(Args, Debug, Clone, Default)]
+pub struct RoutingKeyArgs {
+    /// Card-level pointer for the card id (default: /greentic/cardId).
+    #[arg(long, value_name = "POINTER")]
+    pub card_id_key: Vec<String>,
+    /// Card-level pointer for the flow name (default: /greentic/flow).
+    #[arg(long, value_name = "POINTER")]
+    pub flow_key: Vec<String>,
+    /// Action pointer for the card id (default: /data/cardId).
+    #[arg(long, value_name = "POINTER")]
+    pub action_card_id_key: Vec<String>,
+    /// Action pointer for the flow name (default: /data/flow).
+    #[arg(long, value_name = "POINTER")]
+    pub action_flow_key: Vec<String>,
+    /// Action pointer for the route target step (default: /data/step).
+    #[arg(long, value_name = "POINTER")]
+    pub target_key: Vec<String>,
+}
+
+/// Per-kind severity overrides; a kind is named by its snake_case name or its code
+/// (`missing_target` or `C2P002`). Flags replace the config file's level for a kind; a kind
+/// given several levels takes the strictest.
+#[derive(Args, Debug, Clone, Default)]
+pub struct SeverityArgs {
+    /// Record warnings of this kind without printing them (repeatable).
+    #[arg(long, value_name = "KIND")]
+    pub allow: Vec<WarningKind>,
+    /// Report warnings of this kind, even under --strict (repeatable).
+    #[arg(long, value_name = "KIND")]
+    pub warn: Vec<WarningKind>,
+    /// Fail on warnings of this kind, after reporting every one of them (repeatable).
+    #[arg(long, value_name = "KIND")]
+    pub deny: Vec<WarningKind>,
+}
+
+#[derive(ValueEnum, Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum GraphFormat {
+    Mermaid,
+    Dot,
+    Json,
+}
+
+#[derive(ValueEnum, Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(ValueEnum, Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
 pub enum GroupBy {
     Folder,
     FlowField,
 }
+
+/// Host an Adaptive Card is rendered in; limits the schema version and elements a card may use.
+#[derive(ValueEnum, Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum HostProfile {
+    /// Any renderer implementing schema 1.6.
+    Generic,
+    /// Any renderer implementing schema 1.4.
+    #[value(name = "generic-1.4")]
+    #[serde(rename = "generic-1.4")]
+    Generic14,
+    Teams,
+    Webex,
+    Outlook,
+}
@@ -0,0 +1,346 @@
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::cli::HostProfile;
+use crate::source_map::escape_pointer_token;
+
+/// An Adaptive Card schema version such as `1.5`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct SchemaVersion(u32, u32);
+
+impl SchemaVersion {
+    fn parse(value: &str) -> Option<Self> {
+        let (major, minor) = value.trim().split_once('.')?;
+        Some(Self(major.parse().ok()?, minor.parse().ok()?))
+    }
+}
+
+impl fmt::Display for SchemaVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.0, self.1)
+    }
+}
+
+const LATEST: SchemaVersion = SchemaVersion(1, 6);
+
+/// Element and action types newer than schema 1.0. Unlisted types are either 1.0 or host
+/// extensions and are not checked.
+const TYPES: &[(&str, SchemaVersion)] = &[
+    ("Media", SchemaVersion(1, 1)),
+    ("ActionSet", SchemaVersion(1, 2)),
+    ("RichTextBlock", SchemaVersion(1, 2)),
+    ("TextRun", SchemaVersion(1, 2)),
+    ("Action.ToggleVisibility", SchemaVersion(1, 2)),
+    ("Action.Execute", SchemaVersion(1, 4)),
+    ("Table", SchemaVersion(1, 5)),
+    ("TableRow", SchemaVersion(1, 5)),
+    ("TableCell", SchemaVersion(1, 5)),
+];
+
+/// Properties newer than schema 1.0, by the type they appear on: `*` matches any type and a
+/// trailing `*` matches a prefix (`Input.*`).
+const PROPERTIES: &[(&str, &str, SchemaVersion)] = &[
+    ("Container", "selectAction", SchemaVersion(1, 1)),
+    ("ColumnSet", "selectAction", SchemaVersion(1, 1)),
+    ("Column", "selectAction", SchemaVersion(1, 1)),
+    ("Container", "verticalContentAlignment", SchemaVersion(1, 1)),
+    ("*", "isVisible", SchemaVersion(1, 2)),
+    ("*", "fallback", SchemaVersion(1, 2)),
+    ("Container", "minHeight", SchemaVersion(1, 2)),
+    ("Container", "bleed", SchemaVersion(1, 2)),
+    ("Container", "backgroundImage", SchemaVersion(1, 2)),
+    ("Input.ChoiceSet", "wrap", SchemaVersion(1, 2)),
+    ("Input.Text", "inlineAction", SchemaVersion(1, 2)),
+    ("Action.*", "style", SchemaVersion(1, 2)),
+    ("Input.*", "label", SchemaVersion(1, 3)),
+    ("Input.*", "isRequired", SchemaVersion(1, 3)),
+    ("Input.*", "errorMessage", SchemaVersion(1, 3)),
+    ("Input.Text", "regex", SchemaVersion(1, 3)),
+    ("Action.Submit", "associatedInputs", SchemaVersion(1, 3)),
+    ("AdaptiveCard", "refresh", SchemaVersion(1, 4)),
+    ("AdaptiveCard", "authentication", SchemaVersion(1, 4)),
+    ("AdaptiveCard", "rtl", SchemaVersion(1, 5)),
+    ("Container", "rtl", SchemaVersion(1, 5)),
+    ("Action.*", "isEnabled", SchemaVersion(1, 5)),
+    ("Action.*", "mode", SchemaVersion(1, 5)),
+    ("Action.*", "tooltip", SchemaVersion(1, 5)),
+    ("AdaptiveCard", "metadata", SchemaVersion(1, 6)),
+];
+
+/// Highest schema version a host renders and the types it rejects regardless of version.
+struct Host {
+    name: &'static str,
+    max: SchemaVersion,
+    unsupported: &'static [&'static str],
+}
+
+fn host(profile: HostProfile) -> Host {
+    match profile {
+        HostProfile::Generic => Host {
+            name: "generic",
+            max: LATEST,
+            unsupported: &[],
+        },
+        HostProfile::Generic14 => Host {
+            name: "generic-1.4",
+            max: SchemaVersion(1, 4),
+            unsupported: &[],
+        },
+        HostProfile::Teams => Host {
+            name: "teams",
+            max: SchemaVersion(1, 5),
+            unsupported: &[],
+        },
+        HostProfile::Webex => Host {
+            name: "webex",
+            max: SchemaVersion(1, 3),
+            unsupported: &["Media"],
+        },
+        HostProfile::Outlook => Host {
+            name: "outlook",
+            max: SchemaVersion(1, 4),
+            unsupported: &["Action.Submit", "Media"],
+        },
+    }
+}
+
+/// One compatibility violation and the JSON pointer of the element it concerns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub pointer: String,
+    pub message: String,
+}
+
+/// Elements, actions and properties the card uses that its declared `version` (or the host
+/// profile) does not support, as one issue per violation.
+pub fn compatibility_issues(
+    card: &Value,
+    rel_path: &str,
+    profile: Option<HostProfile>,
+) -> Vec<Issue> {
+    let host = profile.map(host);
+    let mut issues = Vec::new();
+
+    let declared = match card.get("version") {
+        Some(Value::String(raw)) => match SchemaVersion::parse(raw) {
+            Some(version) if version <= LATEST => Some(version),
+            _ => {
+                issues.push(Issue {
+                    pointer: "/version".to_string(),
+                    message: format!("{rel_path} declares unknown Adaptive Card version {raw:?}"),
+                });
+                None
+            }
+        },
+        _ => None,
+    };
+    if let (Some(declared), Some(host)) = (declared, &host)
+        && declared > host.max
+    {
+        issues.push(Issue {
+            pointer: "/version".to_string(),
+            message: format!(
+                "{rel_path} declares version {declared} but {} supports up to {}",
+                host.name, host.max
+            ),
+        });
+    }
+
+    let checker = Checker {
+        rel_path,
+        declared,
+        host: host.as_ref(),
+    };
+    checker.walk(card, String::new(), &mut issues);
+    issues
+}
+
+struct Checker<'a> {
+    rel_path: &'a str,
+    declared: Option<SchemaVersion>,
+    host: Option<&'a Host>,
+}
+
+impl Checker<'_> {
+    fn walk(&self, value: &Value, path: String, issues: &mut Vec<Issue>) {
+        match value {
+            Value::Object(map) => {
+                if let Some(kind) = map.get("type").and_then(Value::as_str) {
+                    self.check_type(kind, &path, issues);
+                    for key in map.keys() {
+                        self.check_property(kind, key, &path, issues);
+                    }
+                }
+                for (key, child) in map {
+                    // Action data is free-form payload, not card schema.
+                    if key != "data" {
+                        self.walk(
+                            child,
+                            format!("{path}/{}", escape_pointer_token(key)),
+                            issues,
+                        );
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    self.walk(item, format!("{path}/{index}"), issues);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_type(&self, kind: &str, path: &str, issues: &mut Vec<Issue>) {
+        if let Some(host) = self.host
+            && host.unsupported.contains(&kind)
+        {
+            issues.push(Issue {
+                pointer: path.to_string(),
+                message: format!(
+                    "{kind}{} in {} is not supported by {}",
+                    location(path),
+                    self.rel_path,
+                    host.name
+                ),
+            });
+            return;
+        }
+        if let Some((_, since)) = TYPES.iter().find(|(name, _)| *name == kind)
+            && let Some(reason) = self.too_new(*since)
+        {
+            issues.push(Issue {
+                pointer: path.to_string(),
+                message: format!(
+                    "{kind}{} in {} requires version {since} ({reason})",
+                    location(path),
+                    self.rel_path
+                ),
+            });
+        }
+    }
+
+    fn check_property(&self, kind: &str, property: &str, path: &str, issues: &mut Vec<Issue>) {
+        let since = PROPERTIES
+            .iter()
+            .filter(|(owner, name, _)| *name == property && owner_matches(owner, kind))
+            .map(|(_, _, since)| *since)
+            .min();
+        if let Some(since) = since
+            && let Some(reason) = self.too_new(since)
+        {
+            issues.push(Issue {
+                pointer: format!("{path}/{}", escape_pointer_token(property)),
+                message: format!(
+                    "{property} on {kind}{} in {} requires version {since} ({reason})",
+                    location(path),
+                    self.rel_path
+                ),
+            });
+        }
+    }
+
+    /// Why a feature introduced in `since` cannot be used, if it cannot.
+    fn too_new(&self, since: SchemaVersion) -> Option<String> {
+        if let Some(declared) = self.declared
+            && since > declared
+        {
+            return Some(format!("card declares {declared}"));
+        }
+        self.host
+            .filter(|host| since > host.max)
+            .map(|host| format!("{} supports up to {}", host.name, host.max))
+    }
+}
+
+fn owner_matches(owner: &str, kind: &str) -> bool {
+    match owner.strip_suffix('*') {
+        Some(prefix) => kind.starts_with(prefix),
+        None => owner == kind,
+    }
+}
+
+fn location(path: &str) -> String {
+    if path.is_empty() {
+        String::new()
+    } else {
+        format!(" at {path}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::compatibility_issues;
+    use crate::cli::HostProfile;
+    use serde_json::{Value, json};
+
+    fn messages(card: &Value, profile: Option<HostProfile>) -> Vec<String> {
+        compatibility_issues(card, "a.json", profile)
+            .into_iter()
+            .map(|issue| issue.message)
+            .collect()
+    }
+
+    #[test]
+    fn reports_features_newer_than_the_declared_version() {
+        let card = json!({
+            "type": "AdaptiveCard",
+            "version": "1.2",
+            "body": [
+                { "type": "Input.Text", "id": "name", "label": "Name", "isVisible": true },
+                { "type": "Table", "columns": [] }
+            ],
+            "actions": [
+                { "type": "Action.Execute", "verb": "go", "data": { "type": "Table", "mode": "x" } }
+            ]
+        });
+        assert_eq!(
+            messages(&card, None),
+            vec![
+                "Action.Execute at /actions/0 in a.json requires version 1.4 (card declares 1.2)",
+                "label on Input.Text at /body/0 in a.json requires version 1.3 (card declares 1.2)",
+                "Table at /body/1 in a.json requires version 1.5 (card declares 1.2)",
+            ]
+        );
+    }
+
+    #[test]
+    fn escapes_keys_in_pointers() {
+        let card = json!({
+            "type": "AdaptiveCard",
+            "version": "1.2",
+            "body": [],
+            "x/y~z": { "type": "Table", "columns": [] }
+        });
+        let issues = compatibility_issues(&card, "a.json", None);
+        assert_eq!(issues[0].pointer, "/x~1y~0z");
+    }
+
+    #[test]
+    fn applies_host_profiles() {
+        let card = json!({
+            "type": "AdaptiveCard",
+            "version": "1.5",
+            "body": [{ "type": "Media", "sources": [] }],
+            "actions": [{ "type": "Action.Submit", "tooltip": "Send" }]
+        });
+        assert!(messages(&card, Some(HostProfile::Generic)).is_empty());
+        assert!(messages(&card, Some(HostProfile::Teams)).is_empty());
+        let webex = compatibility_issues(&card, "a.json", Some(HostProfile::Webex));
+        let pointers: Vec<&str> = webex.iter().map(|issue| issue.pointer.as_str()).collect();
+        assert_eq!(pointers, vec!["/version", "/actions/0/tooltip", "/body/0"]);
+        assert_eq!(
+            messages(&card, Some(HostProfile::Webex)),
+            vec![
+                "a.json declares version 1.5 but webex supports up to 1.3",
+                "tooltip on Action.Submit at /actions/0 in a.json requires version 1.5 (webex supports up to 1.3)",
+                "Media at /body/0 in a.json is not supported by webex",
+            ]
+        );
+        assert_eq!(
+            compatibility_issues(&json!({ "version": "2.0" }), "b.json", None)[0].message,
+            "b.json declares unknown Adaptive Card version \"2.0\""
+        );
+    }
+}
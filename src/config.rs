@@ -0,0 +1,344 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cli::{
+    CardScanArgs, GenerateArgs, GraphFormat, GroupBy, HostProfile, RoutingKeyArgs, SeverityArgs,
+    bool_flag,
+};
+use crate::ir::{Severity, WarningKind};
+use crate::qa_integration::{PromptLimits, prompt_limits_from_arg};
+use crate::scan::{RoutingKeys, ScanConfig};
+
+pub const CONFIG_FILE_NAMES: [&str; 3] = ["cards2pack.yaml", "cards2pack.yml", "cards2pack.toml"];
+
+/// Whether `path` is a project config file, which card scanning must skip.
+pub fn is_config_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| CONFIG_FILE_NAMES.contains(&name))
+}
+
+/// Contents of `cards2pack.yaml` / `cards2pack.toml`; every field is optional and CLI flags win.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectConfig {
+    pub cards: Option<PathBuf>,
+    pub out: Option<PathBuf>,
+    pub name: Option<String>,
+    pub greentic_pack_bin: Option<PathBuf>,
+    pub group_by: Option<GroupBy>,
+    pub flow_field: Option<String>,
+    pub default_flow: Option<String>,
+    pub strict: Option<bool>,
+    pub max_errors: Option<usize>,
+    pub allow: Option<Vec<WarningKind>>,
+    pub warn: Option<Vec<WarningKind>>,
+    pub deny: Option<Vec<WarningKind>>,
+    pub routing_keys: Option<RoutingKeys>,
+    pub include: Option<Vec<String>>,
+    pub exclude: Option<Vec<String>>,
+    pub verbose: Option<bool>,
+    pub prompt: Option<bool>,
+    pub prompt_json: Option<PathBuf>,
+    pub prompt_limits: Option<PromptLimits>,
+    pub expand_templates: Option<bool>,
+    pub graph: Option<GraphFormat>,
+    pub jobs: Option<usize>,
+    pub host: Option<HostProfile>,
+    pub locale_suffixes: Option<bool>,
+}
+
+/// Fully resolved generate options, recorded in `.cards2pack/manifest.json`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct GenerateOptions {
+    pub config_path: Option<PathBuf>,
+    pub cards: PathBuf,
+    pub out: PathBuf,
+    pub name: String,
+    pub greentic_pack_bin: Option<PathBuf>,
+    pub group_by: Option<GroupBy>,
+    pub flow_field: Option<String>,
+    pub default_flow: Option<String>,
+    pub strict: bool,
+    /// Strict-mode error limit; `None` reports every error.
+    pub max_errors: Option<usize>,
+    /// Merged `allow`/`warn`/`deny` overrides by warning kind.
+    #[serde(default)]
+    pub severities: BTreeMap<WarningKind, Severity>,
+    pub routing_keys: RoutingKeys,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub verbose: bool,
+    pub prompt: bool,
+    pub prompt_json: Option<PathBuf>,
+    pub prompt_limits: Option<PromptLimits>,
+    pub expand_templates: bool,
+    pub graph: Option<GraphFormat>,
+    /// Worker threads for scanning and flow emission; `None` uses the available parallelism.
+    pub jobs: Option<usize>,
+    pub host: Option<HostProfile>,
+    /// Read locales from `<card>.<locale>.json` file names.
+    #[serde(default)]
+    pub locale_suffixes: bool,
+    /// Rebuild everything regardless of `.cards2pack/build-state.json`.
+    #[serde(default)]
+    pub force: bool,
+}
+
+pub fn resolve_generate(args: &GenerateArgs) -> Result<GenerateOptions> {
+    let loaded = load_project_config(args.scan.config.as_deref(), args.scan.cards.as_deref())?;
+    let (config_path, config) = match loaded {
+        Some((path, config)) => (Some(path), config),
+        None => (None, ProjectConfig::default()),
+    };
+    let severities = merge_severities(&args.scan.severity, &config);
+
+    let cards = args
+        .scan
+        .cards
+        .clone()
+        .or(config.cards)
+        .ok_or_else(|| anyhow!("--cards is required (or set `cards` in cards2pack.yaml)"))?;
+    let out = args
+        .out
+        .clone()
+        .or(config.out)
+        .ok_or_else(|| anyhow!("--out is required (or set `out` in cards2pack.yaml)"))?;
+    let name = args
+        .name
+        .clone()
+        .or(config.name)
+        .ok_or_else(|| anyhow!("--name is required (or set `name` in cards2pack.yaml)"))?;
+    let prompt = bool_flag(args.prompt, args.no_prompt)
+        .or(config.prompt)
+        .unwrap_or(false);
+    let prompt_json = args.prompt_json.clone().or(config.prompt_json);
+    let cli_prompt_limits = prompt_limits_from_arg(args.prompt_limits.as_deref())?;
+    let prompt_limits = cli_prompt_limits.or(config.prompt_limits);
+
+    if prompt_json.is_some() && !prompt {
+        if args.prompt_json.is_some() {
+            bail!("--prompt-json requires --prompt");
+        }
+        bail!("`prompt_json` in cards2pack.yaml requires --prompt (or `prompt: true`)");
+    }
+    if prompt_limits.is_some() && !prompt {
+        if args.prompt_limits.is_some() {
+            bail!("--prompt-limits requires --prompt");
+        }
+        bail!("`prompt_limits` in cards2pack.yaml requires --prompt (or `prompt: true`)");
+    }
+
+    Ok(GenerateOptions {
+        config_path,
+        cards,
+        out,
+        name,
+        greentic_pack_bin: args.greentic_pack_bin.clone().or(config.greentic_pack_bin),
+        group_by: args.scan.group_by.or(config.group_by),
+        flow_field: args.scan.flow_field.clone().or(config.flow_field),
+        default_flow: args.scan.default_flow.clone().or(config.default_flow),
+        strict: bool_flag(args.scan.strict, args.scan.no_strict)
+            .or(config.strict)
+            .unwrap_or(false),
+        max_errors: args.scan.max_errors.or(config.max_errors),
+        severities,
+        routing_keys: merge_routing_keys(&args.scan.routing_keys, config.routing_keys),
+        include: merge_globs(&args.scan.include, config.include),
+        exclude: merge_globs(&args.scan.exclude, config.exclude),
+        verbose: bool_flag(args.verbose, args.no_verbose)
+            .or(config.verbose)
+            .unwrap_or(false),
+        prompt,
+        prompt_json,
+        prompt_limits,
+        expand_templates: bool_flag(args.expand_templates, args.no_expand_templates)
+            .or(config.expand_templates)
+            .unwrap_or(false),
+        graph: args.graph.or(config.graph),
+        jobs: args.scan.jobs.or(config.jobs),
+        host: args.scan.host.or(config.host),
+        locale_suffixes: bool_flag(args.scan.locale_suffixes, args.scan.no_locale_suffixes)
+            .or(config.locale_suffixes)
+            .unwrap_or(false),
+        force: args.force,
+    })
+}
+
+/// Resolves the `scan`/`graph` flags against the project config; flags win.
+pub fn resolve_scan(args: &CardScanArgs) -> Result<ScanConfig> {
+    let config = load_project_config(args.config.as_deref(), args.cards.as_deref())?
+        .map(|(_, config)| config)
+        .unwrap_or_default();
+
+    let cards_dir = args
+        .cards
+        .clone()
+        .or(config.cards.clone())
+        .ok_or_else(|| anyhow!("--cards is required (or set `cards` in cards2pack.yaml)"))?;
+    let severities = merge_severities(&args.severity, &config);
+    Ok(ScanConfig {
+        cards_dir,
+        group_by: args.group_by.or(config.group_by),
+        flow_field: args.flow_field.clone().or(config.flow_field),
+        default_flow: args.default_flow.clone().or(config.default_flow),
+        strict: bool_flag(args.strict, args.no_strict)
+            .or(config.strict)
+            .unwrap_or(false),
+        max_errors: args.max_errors.or(config.max_errors),
+        severities,
+        routing_keys: merge_routing_keys(&args.routing_keys, config.routing_keys),
+        include: merge_globs(&args.include, config.include),
+        exclude: merge_globs(&args.exclude, config.exclude),
+        jobs: args.jobs.or(config.jobs),
+        host: args.host.or(config.host),
+        locale_suffixes: bool_flag(args.locale_suffixes, args.no_locale_suffixes)
+            .or(config.locale_suffixes)
+            .unwrap_or(false),
+        ..ScanConfig::default()
+    })
+}
+
+/// Globs given on the command line replace the configured list.
+fn merge_globs(flags: &[String], config: Option<Vec<String>>) -> Vec<String> {
+    if flags.is_empty() {
+        config.unwrap_or_default()
+    } else {
+        flags.to_vec()
+    }
+}
+
+/// Applies the config's `allow`/`warn`/`deny` lists, then the flags; within each source a kind
+/// listed at several levels takes the strictest.
+fn merge_severities(
+    args: &SeverityArgs,
+    config: &ProjectConfig,
+) -> BTreeMap<WarningKind, Severity> {
+    let mut severities = BTreeMap::new();
+    for (allow, warn, deny) in [
+        (
+            config.allow.as_deref().unwrap_or_default(),
+            config.warn.as_deref().unwrap_or_default(),
+            config.deny.as_deref().unwrap_or_default(),
+        ),
+        (&args.allow[..], &args.warn[..], &args.deny[..]),
+    ] {
+        let mut level = BTreeMap::new();
+        for (kinds, severity) in [
+            (allow, Severity::Allow),
+            (warn, Severity::Warn),
+            (deny, Severity::Deny),
+        ] {
+            for kind in kinds {
+                level.insert(*kind, severity);
+            }
+        }
+        severities.extend(level);
+    }
+    severities
+}
+
+/// Starts from the config's `routing_keys` (or the defaults) and replaces every key given on
+/// the command line.
+fn merge_routing_keys(args: &RoutingKeyArgs, config: Option<RoutingKeys>) -> RoutingKeys {
+    let mut keys = config.unwrap_or_default();
+    for (flag, key) in [
+        (&args.card_id_key, &mut keys.card_id),
+        (&args.flow_key, &mut keys.flow),
+        (&args.action_card_id_key, &mut keys.action_card_id),
+        (&args.action_flow_key, &mut keys.action_flow),
+        (&args.target_key, &mut keys.target),
+    ] {
+        if !flag.is_empty() {
+            *key = flag.clone();
+        }
+    }
+    keys
+}
+
+/// Loads the explicit `--config` file, or the first config file found in the cards directory,
+/// its parent, or (without `--cards`) the current directory.
+pub fn load_project_config(
+    explicit: Option<&Path>,
+    cards_dir: Option<&Path>,
+) -> Result<Option<(PathBuf, ProjectConfig)>> {
+    let path = match explicit {
+        Some(path) => {
+            if !path.is_file() {
+                bail!("config file does not exist: {}", path.display());
+            }
+            path.to_path_buf()
+        }
+        None => match discover_config(cards_dir)? {
+            Some(path) => path,
+            None => return Ok(None),
+        },
+    };
+
+    let contents =
+        fs::read_to_string(&path).with_context(|| format!("read config {}", path.display()))?;
+    let mut config = parse_config(&path, &contents)?;
+    let base = path.parent().unwrap_or_else(|| Path::new("."));
+    for field in [
+        &mut config.cards,
+        &mut config.out,
+        &mut config.greentic_pack_bin,
+        &mut config.prompt_json,
+    ] {
+        if let Some(value) = field.as_mut()
+            && value.is_relative()
+        {
+            *value = base.join(&*value);
+        }
+    }
+
+    Ok(Some((path, config)))
+}
+
+fn discover_config(cards_dir: Option<&Path>) -> Result<Option<PathBuf>> {
+    let mut dirs = Vec::new();
+    match cards_dir {
+        Some(cards_dir) => {
+            dirs.push(cards_dir.to_path_buf());
+            if let Some(parent) = cards_dir.parent() {
+                dirs.push(if parent.as_os_str().is_empty() {
+                    PathBuf::from(".")
+                } else {
+                    parent.to_path_buf()
+                });
+            }
+        }
+        None => dirs.push(env::current_dir().context("resolve current directory")?),
+    }
+
+    for dir in dirs {
+        for name in CONFIG_FILE_NAMES {
+            let candidate = dir.join(name);
+            if candidate.is_file() {
+                return Ok(Some(candidate));
+            }
+        }
+    }
+    Ok(None)
+}
+
+fn parse_config(path: &Path, contents: &str) -> Result<ProjectConfig> {
+    let is_toml = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+    if is_toml {
+        toml::from_str(contents).with_context(|| format!("invalid config {}", path.display()))
+    } else if contents.trim().is_empty() {
+        Ok(ProjectConfig::default())
+    } else {
+        serde_yaml_bw::from_str(contents)
+            .with_context(|| format!("invalid config {}", path.display()))
+    }
+}
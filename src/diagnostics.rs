@@ -1,9 +1,227 @@
-use crate::ir::{Diagnostics, FlowSummary, Warning, WarningKind};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use anyhow::Result;
+
+use crate::ir::{Diagnostics, FlowSummary, Manifest, RouteTarget, Severity, Warning, WarningKind};
 
 pub fn warning(kind: WarningKind, message: impl Into<String>) -> Warning {
     Warning {
         kind,
         message: message.into(),
+        location: None,
+        severity: kind.default_severity(),
+        card_id: None,
+        flow: None,
+        rel_path: None,
+        target: None,
+        route_key: None,
+        tool: None,
+    }
+}
+
+static NO_OVERRIDES: BTreeMap<WarningKind, Severity> = BTreeMap::new();
+
+/// How each warning kind is treated: `--allow`/`--warn`/`--deny` overrides on top of the kind's
+/// default severity. `--strict` denies the checks it covers unless their kind is overridden.
+#[derive(Debug, Clone, Copy)]
+pub struct SeverityPolicy<'a> {
+    pub strict: bool,
+    pub overrides: &'a BTreeMap<WarningKind, Severity>,
+}
+
+impl SeverityPolicy<'static> {
+    /// Plain `--strict` (or lenient) behaviour without overrides.
+    pub fn strict(strict: bool) -> Self {
+        Self {
+            strict,
+            overrides: &NO_OVERRIDES,
+        }
+    }
+}
+
+impl SeverityPolicy<'_> {
+    /// Severity of `kind` after overrides; `--strict` does not change it.
+    pub fn severity(&self, kind: WarningKind) -> Severity {
+        self.overrides
+            .get(&kind)
+            .copied()
+            .unwrap_or_else(|| kind.default_severity())
+    }
+
+    /// Whether a check covered by `--strict` fails the run for `kind`.
+    pub fn denies(&self, kind: WarningKind) -> bool {
+        match self.overrides.get(&kind) {
+            Some(severity) => *severity == Severity::Deny,
+            None => self.strict || kind.default_severity() == Severity::Deny,
+        }
+    }
+
+    /// Reports a finding of a check covered by `--strict`: into `errors` when its kind is denied,
+    /// otherwise into `warnings`.
+    pub fn report(&self, finding: Warning, warnings: &mut Vec<Warning>, errors: &mut StrictErrors) {
+        if self.denies(finding.kind) {
+            errors.push(finding);
+        } else {
+            warnings.push(finding);
+        }
+    }
+
+    /// Stamps each warning with its effective severity and moves denied ones into `errors`.
+    pub fn apply(&self, warnings: &mut Vec<Warning>, errors: &mut StrictErrors) {
+        let mut kept = Vec::with_capacity(warnings.len());
+        for mut warning in warnings.drain(..) {
+            warning.severity = self.severity(warning.kind);
+            if warning.severity == Severity::Deny {
+                errors.push(warning);
+            } else {
+                kept.push(warning);
+            }
+        }
+        *warnings = kept;
+    }
+}
+
+/// Strict-mode violations collected across a run, so every one is reported at once rather than
+/// failing on the first.
+#[derive(Debug, Default)]
+pub struct StrictErrors {
+    /// Stop collecting after this many errors; `None` or 0 collects all of them.
+    max: Option<usize>,
+    /// Findings in the order they were found, with their location.
+    errors: Vec<Warning>,
+}
+
+impl StrictErrors {
+    pub fn new(max: Option<usize>) -> Self {
+        Self {
+            max: max.filter(|max| *max > 0),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Records a violation as a denied finding; once the maximum is reached further ones are
+    /// dropped. The run still fails at the next [`finish_with`](Self::finish_with), so the failure
+    /// carries every warning found up to then.
+    pub fn push(&mut self, mut error: Warning) {
+        if self.is_full() {
+            return;
+        }
+        error.severity = Severity::Deny;
+        self.errors.push(error);
+    }
+
+    /// Appends errors collected separately (e.g. by a worker), keeping their order.
+    pub fn extend(&mut self, other: StrictErrors) {
+        for error in other.errors {
+            self.push(error);
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.max.is_some_and(|max| self.errors.len() >= max)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// The collected findings, e.g. to add fields known only after they were found.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Warning> {
+        self.errors.iter_mut()
+    }
+
+    /// Fails with every collected error, grouped by file, if there are any.
+    pub fn finish(self) -> Result<()> {
+        self.finish_with(&[])
+    }
+
+    /// Like [`finish`](Self::finish), but the failure also carries the run's other `warnings`.
+    pub fn finish_with(self, warnings: &[Warning]) -> Result<()> {
+        if self.errors.is_empty() {
+            return Ok(());
+        }
+        Err(self.failure(warnings).into())
+    }
+
+    fn failure(&self, warnings: &[Warning]) -> StrictFailure {
+        StrictFailure {
+            report: self.report(),
+            findings: warnings.iter().chain(&self.errors).cloned().collect(),
+        }
+    }
+
+    fn report(&self) -> String {
+        let mut files: Vec<(String, Vec<&Warning>)> = Vec::new();
+        for error in &self.errors {
+            let file = group_label(error);
+            match files.iter_mut().find(|(name, _)| *name == file) {
+                Some((_, errors)) => errors.push(error),
+                None => files.push((file, vec![error])),
+            }
+        }
+
+        let count = self.errors.len();
+        let mut output = format!(
+            "{count} finding{} denied in {} file{}",
+            if count == 1 { "" } else { "s" },
+            files.len(),
+            if files.len() == 1 { "" } else { "s" }
+        );
+        if self.max == Some(count) {
+            output.push_str(&format!(" (stopped at --max-errors {count})"));
+        }
+        output.push(':');
+        for (file, errors) in files {
+            output.push_str(&format!("\n{file}:"));
+            for error in errors {
+                let position = match error.location.as_ref().and_then(|l| l.line.zip(l.column)) {
+                    Some((line, column)) => format!("{line}:{column}: "),
+                    None => String::new(),
+                };
+                output.push_str(&format!(
+                    "\n  - {position}[{}] {}",
+                    error.kind.name(),
+                    error.message
+                ));
+            }
+        }
+        output
+    }
+}
+
+/// The error a run fails with when strict mode or `--deny` rejects findings. It keeps every
+/// finding (the denied ones with [`Severity::Deny`]) so they can still be written out, e.g. as
+/// SARIF; recover it with `anyhow::Error::downcast_ref`.
+#[derive(Debug)]
+pub struct StrictFailure {
+    report: String,
+    pub findings: Vec<Warning>,
+}
+
+impl fmt::Display for StrictFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.report)
+    }
+}
+
+impl std::error::Error for StrictFailure {}
+
+/// What a strict-mode error is grouped under: the card path relative to the cards directory,
+/// else the file it points at, else the tool or flow it concerns.
+fn group_label(error: &Warning) -> String {
+    if let Some(rel_path) = &error.rel_path {
+        return rel_path.clone();
+    }
+    if let Some(location) = &error.location {
+        return location.file.clone();
+    }
+    if let Some(tool) = &error.tool {
+        return tool.clone();
+    }
+    match &error.flow {
+        Some(flow) => format!("flow {flow}"),
+        None => "(no file)".to_string(),
     }
 }
 
@@ -44,29 +262,64 @@ pub fn summarize(diagnostics: &Diagnostics, warnings: &[Warning]) -> String {
 
     output.push_str(&format!("Warnings: {}\n", diagnostics.warnings_count));
 
-    for warning in warnings.iter().take(5) {
-        output.push_str(&format!(
-            "  - [{}] {}\n",
-            format_kind(&warning.kind),
-            warning.message
-        ));
+    for warning in warnings.iter().filter(|w| !w.is_allowed()).take(5) {
+        output.push_str(&format!("  - [{}] {}\n", warning.kind.name(), warning));
     }
 
     output.trim_end().to_string()
 }
 
-fn format_kind(kind: &WarningKind) -> &'static str {
-    match kind {
-        WarningKind::Inconsistent => "inconsistent",
-        WarningKind::MissingTarget => "missing_target",
-        WarningKind::MissingFlow => "missing_flow",
-        WarningKind::MissingCardId => "missing_card_id",
-        WarningKind::DuplicateCardId => "duplicate_card_id",
-        WarningKind::InvalidJson => "invalid_json",
-        WarningKind::IgnoredFile => "ignored_file",
-        WarningKind::PackOutput => "pack_output",
-        WarningKind::Validation => "validation",
+pub fn summarize_scan(manifest: &Manifest) -> String {
+    let mut output = String::new();
+    output.push_str(&format!("Cards: {}\n", manifest.input.cards_dir.display()));
+    output.push_str(&format!(
+        "Cards processed: {}\n",
+        manifest.diagnostics.cards_processed
+    ));
+
+    output.push_str("Flows:\n");
+    if manifest.flows.is_empty() {
+        output.push_str("  (none)\n");
     }
+    for flow in &manifest.flows {
+        output.push_str(&format!(
+            "  - {} ({} cards)\n",
+            flow.flow_name,
+            flow.cards.len()
+        ));
+        for card in &flow.cards {
+            match &card.locale {
+                Some(locale) => output.push_str(&format!(
+                    "    - {} [{}] ({})\n",
+                    card.card_id, locale, card.rel_path
+                )),
+                None => output.push_str(&format!("    - {} ({})\n", card.card_id, card.rel_path)),
+            }
+            for action in &card.actions {
+                let title = action.title.as_deref().unwrap_or("(untitled)");
+                let target = match &action.target {
+                    Some(RouteTarget::Step(step)) => format!(" -> step {step}"),
+                    Some(RouteTarget::CardId(card_id)) => format!(" -> cardId {card_id}"),
+                    Some(RouteTarget::Verb(verb)) => format!(" -> verb {verb}"),
+                    None => String::new(),
+                };
+                output.push_str(&format!(
+                    "      - {} \"{}\"{} @ {}\n",
+                    action.action_type, title, target, action.path
+                ));
+            }
+        }
+    }
+
+    output.push_str(&format!(
+        "Warnings: {}\n",
+        manifest.diagnostics.warnings_count
+    ));
+    for warning in manifest.warnings.iter().filter(|w| !w.is_allowed()) {
+        output.push_str(&format!("  - [{}] {}\n", warning.kind.name(), warning));
+    }
+
+    output.trim_end().to_string()
 }
 
 pub fn build_diagnostics(
@@ -75,8 +328,9 @@ pub fn build_diagnostics(
     flow_paths: Vec<String>,
     flows: Vec<FlowSummary>,
     cards_processed: usize,
-    warnings_count: usize,
+    warnings: &[Warning],
 ) -> Diagnostics {
+    let warnings_count = warnings.iter().filter(|w| !w.is_allowed()).count();
     Diagnostics {
         workspace_root,
         dist_artifact,
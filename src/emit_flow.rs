@@ -1,14 +1,16 @@
 use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsStr;
 use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
 
 use anyhow::{Context, Result, bail};
 use serde_json::json;
 
-use crate::diagnostics::warning;
+use crate::diagnostics::{StrictErrors, warning};
 use crate::graph::{FlowGraph, FlowNode};
-use crate::ir::{Warning, WarningKind};
+use crate::ir::{Location, Warning, WarningKind};
 
 const BEGIN_MARKER: &str = "# BEGIN GENERATED (cards2pack)";
 const END_MARKER: &str = "# END GENERATED (cards2pack)";
@@ -23,8 +25,15 @@ pub fn emit_flow(
     fs::create_dir_all(&flows_dir)
         .with_context(|| format!("failed to create {}", flows_dir.display()))?;
 
-    let path = flows_dir.join("main.ygtc");
-    let (generated, warnings) = generate_flow_with_cli(graph, workspace_root, strict)?;
+    let path = flows_dir.join(format!("{}.ygtc", graph.flow_name));
+    let (generated, mut warnings) = generate_flow_with_cli(graph, workspace_root, strict)?;
+    // Emission warnings concern the generated flow rather than a card; like the strict cycle
+    // error, they name it relative to the workspace so locations are the same on every machine.
+    let flow_file = format!("flows/{}.ygtc", graph.flow_name);
+    for warning in &mut warnings {
+        warning.location = Some(Location::file(flow_file.as_str()));
+        warning.flow = Some(graph.flow_name.clone());
+    }
     let block = format!("{BEGIN_MARKER}\n{generated}\n{END_MARKER}\n");
 
     let next_contents = if path.exists() {
@@ -75,6 +84,8 @@ fn generate_flow_with_cli(
     ])?;
 
     let mut warnings = Vec::new();
+    let mut errors = StrictErrors::default();
+    let flow_file = format!("flows/{}.ygtc", graph.flow_name);
     let order = resolve_node_order(graph);
     let mut created: BTreeSet<String> = BTreeSet::new();
 
@@ -87,34 +98,64 @@ fn generate_flow_with_cli(
         let (routes, skipped) = resolve_routes(node, &created);
         if !skipped.is_empty() {
             if strict {
-                bail!(
-                    "unable to emit routing for {} due to cycle/ordering: {}",
-                    node_id,
-                    skipped.join(", ")
+                errors.push(
+                    warning(
+                        WarningKind::Inconsistent,
+                        format!(
+                            "unable to emit routing for {} due to cycle/ordering: {}",
+                            node_id,
+                            skipped.join(", ")
+                        ),
+                    )
+                    .at(Location::file(flow_file.as_str()))
+                    .flow(&graph.flow_name)
+                    .card_id(&node_id),
                 );
-            }
-            for target in skipped {
-                warnings.push(warning(
-                    WarningKind::Inconsistent,
-                    format!(
-                        "routing from {} to {} omitted due to ordering; check for cycles",
-                        node_id, target
-                    ),
-                ));
+            } else {
+                for target in skipped {
+                    let mut omitted = warning(
+                        WarningKind::Inconsistent,
+                        format!(
+                            "routing from {} to {} omitted due to ordering; check for cycles",
+                            node_id, target
+                        ),
+                    )
+                    .card_id(&node_id);
+                    if let Some(route) = node.routes.iter().find(|route| route.target == target) {
+                        omitted = omitted.route_key(&route.key);
+                    }
+                    warnings.push(omitted.target(target));
+                }
             }
         }
 
         let card_path_value = if let Some(card_path) = &node.card_path {
             card_path.clone()
         } else {
-            warnings.push(warning(
-                WarningKind::MissingTarget,
-                format!("stub node {} emitted without card_path", node_id),
-            ));
+            warnings.push(
+                warning(
+                    WarningKind::MissingTarget,
+                    format!("stub node {} emitted without card_path", node_id),
+                )
+                .target(&node_id),
+            );
             "TODO".to_string()
         };
         let needs_interaction = !node.routes.is_empty();
-        let payload = build_card_payload(&node_id, &card_path_value, needs_interaction);
+        let verbs: BTreeMap<&str, &str> = node
+            .routes
+            .iter()
+            .filter(|route| routes.contains(&route.target))
+            .filter_map(|route| Some((route.verb.as_deref()?, route.target.as_str())))
+            .collect();
+        let payload = build_card_payload(
+            &node_id,
+            &card_path_value,
+            node.data_path.as_deref(),
+            &node.locales,
+            needs_interaction,
+            &verbs,
+        );
 
         let mut args = vec![
             "add-step".to_string(),
@@ -133,9 +174,10 @@ fn generate_flow_with_cli(
 
         push_routing_flags(&mut args, &node_id, &routes, &mut warnings);
 
-        run_greentic_flow_strings(&args)?;
+        run_greentic_flow(&args)?;
         created.insert(node_id);
     }
+    errors.finish_with(&warnings)?;
 
     let contents = fs::read_to_string(&tmp_flow)
         .with_context(|| format!("failed to read {}", tmp_flow.display()))?;
@@ -218,10 +260,27 @@ fn resolve_node_order(graph: &FlowGraph) -> Vec<String> {
     ordered
 }
 
-fn build_card_payload(node_id: &str, card_path: &str, needs_interaction: bool) -> String {
+/// Builds the add-step payload. `data_path` points at template sample data, and `verbs` maps
+/// `Action.Execute` verbs to their target node so the runtime can dispatch Universal Actions by verb.
+fn build_card_payload(
+    node_id: &str,
+    card_path: &str,
+    data_path: Option<&str>,
+    locales: &BTreeMap<String, String>,
+    needs_interaction: bool,
+    verbs: &BTreeMap<&str, &str>,
+) -> String {
     let mut input = serde_json::Map::new();
     input.insert("card_source".to_string(), json!("asset"));
-    input.insert("card_spec".to_string(), json!({ "asset_path": card_path }));
+    let mut card_spec = json!({ "asset_path": card_path });
+    if let Some(data_path) = data_path {
+        card_spec["data_asset_path"] = json!(data_path);
+    }
+    if !locales.is_empty() {
+        // The runtime renders the variant for the session locale and falls back to `asset_path`.
+        card_spec["localized_asset_paths"] = json!(locales);
+    }
+    input.insert("card_spec".to_string(), card_spec);
     input.insert("mode".to_string(), json!("renderAndValidate"));
     input.insert("node_id".to_string(), json!(node_id));
     input.insert("payload".to_string(), json!({}));
@@ -229,16 +288,24 @@ fn build_card_payload(node_id: &str, card_path: &str, needs_interaction: bool) -
     input.insert("state".to_string(), json!({}));
     input.insert("validation_mode".to_string(), json!("warn"));
     if needs_interaction {
+        let interaction_type = if verbs.is_empty() {
+            "Submit"
+        } else {
+            "Execute"
+        };
         input.insert(
             "interaction".to_string(),
             json!({
                 "action_id": "action-1",
                 "card_instance_id": node_id,
-                "interaction_type": "Submit",
+                "interaction_type": interaction_type,
                 "raw_inputs": {}
             }),
         );
     }
+    if !verbs.is_empty() {
+        input.insert("verb_routes".to_string(), json!(verbs));
+    }
     let call_payload = serde_json::Value::Object(input.clone());
     let mut call = serde_json::Map::new();
     call.insert("op".to_string(), json!("render"));
@@ -257,10 +324,13 @@ fn push_routing_flags(
 ) {
     match routes.len() {
         0 => {
-            warnings.push(warning(
-                WarningKind::MissingTarget,
-                format!("no routes for {}; using routing-out", node_id),
-            ));
+            warnings.push(
+                warning(
+                    WarningKind::NoRoutes,
+                    format!("no routes for {}; using routing-out", node_id),
+                )
+                .card_id(node_id),
+            );
             args.push("--routing-out".to_string());
         }
         1 => {
@@ -274,30 +344,19 @@ fn push_routing_flags(
     }
 }
 
-fn run_greentic_flow(args: &[&str]) -> Result<()> {
-    let status = Command::new("greentic-flow")
-        .args(args)
-        .status()
-        .with_context(|| format!("failed to run greentic-flow {}", args.join(" ")))?;
-    if !status.success() {
-        bail!(
-            "greentic-flow command failed: greentic-flow {}",
-            args.join(" ")
-        );
-    }
-    Ok(())
-}
-
-fn run_greentic_flow_strings(args: &[String]) -> Result<()> {
+fn run_greentic_flow(args: &[impl AsRef<OsStr>]) -> Result<()> {
+    let command_line = args
+        .iter()
+        .map(|arg| arg.as_ref().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ");
     let status = Command::new("greentic-flow")
         .args(args)
+        .stdout(Stdio::from(io::stderr()))
         .status()
-        .with_context(|| format!("failed to run greentic-flow {}", args.join(" ")))?;
+        .with_context(|| format!("failed to run greentic-flow {command_line}"))?;
     if !status.success() {
-        bail!(
-            "greentic-flow command failed: greentic-flow {}",
-            args.join(" ")
-        );
+        bail!("greentic-flow command failed: greentic-flow {command_line}");
     }
     Ok(())
 }
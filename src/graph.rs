@@ -1,9 +1,9 @@
 use std::collections::{BTreeMap, BTreeSet};
 
-use anyhow::{Result, bail};
+use anyhow::Result;
 
-use crate::diagnostics::warning;
-use crate::ir::{FlowGroup, RouteTarget, Warning, WarningKind};
+use crate::diagnostics::{SeverityPolicy, StrictErrors, warning};
+use crate::ir::{CardDoc, FlowGroup, RouteTarget, Warning, WarningKind};
 
 #[derive(Debug)]
 pub struct FlowGraph {
@@ -16,6 +16,10 @@ pub struct FlowGraph {
 pub struct FlowNode {
     pub name: String,
     pub card_path: Option<String>,
+    /// Template sample data packaged next to the card.
+    pub data_path: Option<String>,
+    /// Asset path of each localized variant, by locale; `card_path` is the fallback.
+    pub locales: BTreeMap<String, String>,
     pub routes: Vec<RouteEdge>,
     pub stub: bool,
 }
@@ -24,25 +28,78 @@ pub struct FlowNode {
 pub struct RouteEdge {
     pub key: String,
     pub target: String,
+    /// `Action.Execute` verb that selects this route at runtime.
+    pub verb: Option<String>,
 }
 
+impl FlowGraph {
+    /// First non-stub node in name order; the README and graph exports treat it as the entry.
+    pub fn entry_node(&self) -> Option<&FlowNode> {
+        self.nodes.values().find(|node| !node.stub)
+    }
+}
+
+/// Builds the flow graph for one flow. In strict mode every violation is collected and reported
+/// together.
 pub fn build_flow_graph(group: &FlowGroup, strict: bool) -> Result<FlowGraph> {
+    let mut errors = StrictErrors::default();
+    let graph = build_flow_graph_collecting(group, SeverityPolicy::strict(strict), &mut errors)?;
+    errors.finish_with(&graph.warnings)?;
+    Ok(graph)
+}
+
+/// Like [`build_flow_graph`], but applies `policy` and leaves violations in `errors` for the
+/// caller to report alongside the rest of the run's.
+pub fn build_flow_graph_collecting(
+    group: &FlowGroup,
+    policy: SeverityPolicy<'_>,
+    errors: &mut StrictErrors,
+) -> Result<FlowGraph> {
     let mut nodes: BTreeMap<String, FlowNode> = BTreeMap::new();
     let mut warnings: Vec<Warning> = Vec::new();
 
+    let mut variants: BTreeMap<&str, Vec<&CardDoc>> = BTreeMap::new();
     for card in &group.cards {
-        nodes
-            .entry(card.card_id.clone())
-            .or_insert_with(|| FlowNode {
-                name: card.card_id.clone(),
-                card_path: Some(format!("assets/cards/{}", card.rel_path)),
+        variants
+            .entry(card.card_id.as_str())
+            .or_default()
+            .push(card);
+    }
+    check_locales(&group.flow_name, &variants, policy, &mut warnings, errors);
+
+    for (card_id, cards) in &variants {
+        let primary = primary_variant(cards);
+        let locales = cards
+            .iter()
+            .filter_map(|card| {
+                let locale = card.locale.clone()?;
+                Some((locale, format!("assets/cards/{}", card.asset_path)))
+            })
+            .collect();
+        nodes.insert(
+            card_id.to_string(),
+            FlowNode {
+                name: card_id.to_string(),
+                card_path: Some(format!("assets/cards/{}", primary.asset_path)),
+                data_path: primary
+                    .data_asset_path
+                    .as_ref()
+                    .map(|path| format!("assets/cards/{path}")),
+                locales,
                 routes: Vec::new(),
                 stub: false,
-            });
+            },
+        );
     }
 
     for card in &group.cards {
+        // Routes come from the primary variant; check_locales compared the others against it.
+        if !std::ptr::eq(primary_variant(&variants[card.card_id.as_str()]), card) {
+            continue;
+        }
         let mut used_keys: BTreeSet<String> = BTreeSet::new();
+        // The runtime picks a route by verb, so each verb may select only one target.
+        let mut verb_targets: BTreeMap<&str, String> = BTreeMap::new();
         let mut routes = Vec::new();
 
         for (index, action) in card.actions.iter().enumerate() {
@@ -51,32 +108,44 @@ pub fn build_flow_graph(group: &FlowGroup, strict: bool) -> Result<FlowGraph> {
                 None => continue,
             };
 
-            let target_name = match target {
-                RouteTarget::Step(name) => name.clone(),
-                RouteTarget::CardId(name) => name.clone(),
-            };
+            let target_name = target.name().to_string();
 
             if !nodes.contains_key(&target_name) {
-                if strict {
-                    bail!(
-                        "missing target {} referenced from card {} in flow {}",
-                        target_name,
-                        card.card_id,
-                        group.flow_name
+                if policy.denies(WarningKind::MissingTarget) {
+                    // No stub: later references to the same target are reported too.
+                    errors.push(
+                        warning(
+                            WarningKind::MissingTarget,
+                            format!(
+                                "missing target {} referenced from card {} in flow {}",
+                                target_name, card.card_id, group.flow_name
+                            ),
+                        )
+                        .at(card.source.locate(&action.path))
+                        .for_card(card)
+                        .target(&target_name),
                     );
+                    continue;
                 }
-                warnings.push(warning(
-                    WarningKind::MissingTarget,
-                    format!(
-                        "missing target {} referenced from card {} in flow {}; creating stub",
-                        target_name, card.card_id, group.flow_name
-                    ),
-                ));
+                warnings.push(
+                    warning(
+                        WarningKind::MissingTarget,
+                        format!(
+                            "missing target {} referenced from card {} in flow {}; creating stub",
+                            target_name, card.card_id, group.flow_name
+                        ),
+                    )
+                    .at(card.source.locate(&action.path))
+                    .for_card(card)
+                    .target(&target_name),
+                );
                 nodes.insert(
                     target_name.clone(),
                     FlowNode {
                         name: target_name.clone(),
                         card_path: None,
+                        data_path: None,
+                        locales: BTreeMap::new(),
                         routes: Vec::new(),
                         stub: true,
                     },
@@ -90,20 +159,55 @@ pub fn build_flow_graph(group: &FlowGroup, strict: bool) -> Result<FlowGraph> {
                     suffix += 1;
                 }
                 let new_key = format!("{}-{}", key, suffix);
-                warnings.push(warning(
-                    WarningKind::Inconsistent,
-                    format!(
-                        "duplicate route key {} in card {}; renamed to {}",
-                        key, card.card_id, new_key
-                    ),
-                ));
+                warnings.push(
+                    warning(
+                        WarningKind::Inconsistent,
+                        format!(
+                            "duplicate route key {} in card {}; renamed to {}",
+                            key, card.card_id, new_key
+                        ),
+                    )
+                    .at(card.source.locate(&action.path))
+                    .for_card(card)
+                    .target(&target_name)
+                    .route_key(&new_key),
+                );
                 key = new_key;
             }
             used_keys.insert(key.clone());
 
+            let mut verb = action.verb.clone();
+            if let Some(action_verb) = action.verb.as_deref() {
+                match verb_targets.get(action_verb) {
+                    Some(first) if *first != target_name => {
+                        policy.report(
+                            warning(
+                                WarningKind::Inconsistent,
+                                format!(
+                                    "verb {} in card {} routes to both {} and {}; keeping {}",
+                                    action_verb, card.card_id, first, target_name, first
+                                ),
+                            )
+                            .at(card.source.locate(&action.path))
+                            .for_card(card)
+                            .target(&target_name)
+                            .route_key(&key),
+                            &mut warnings,
+                            errors,
+                        );
+                        verb = None;
+                    }
+                    Some(_) => {}
+                    None => {
+                        verb_targets.insert(action_verb, target_name.clone());
+                    }
+                }
+            }
+
             routes.push(RouteEdge {
                 key,
                 target: target_name,
+                verb,
             });
         }
 
@@ -112,6 +216,7 @@ pub fn build_flow_graph(group: &FlowGroup, strict: bool) -> Result<FlowGraph> {
         }
     }
 
+    policy.apply(&mut warnings, errors);
     Ok(FlowGraph {
         flow_name: group.flow_name.clone(),
         nodes,
@@ -119,18 +224,94 @@ pub fn build_flow_graph(group: &FlowGroup, strict: bool) -> Result<FlowGraph> {
     })
 }
 
+/// The variant a node's `card_path` and routes come from: the card without a locale, else the
+/// first locale in order.
+fn primary_variant<'a>(cards: &[&'a CardDoc]) -> &'a CardDoc {
+    cards
+        .iter()
+        .min_by(|left, right| left.locale.cmp(&right.locale))
+        .copied()
+        .expect("every card id has at least one variant")
+}
+
+/// Localized variants must route like their primary variant, and a localized card should have
+/// a variant for every locale used in the flow.
+fn check_locales(
+    flow_name: &str,
+    variants: &BTreeMap<&str, Vec<&CardDoc>>,
+    policy: SeverityPolicy<'_>,
+    warnings: &mut Vec<Warning>,
+    errors: &mut StrictErrors,
+) {
+    let all_locales: BTreeSet<&str> = variants
+        .values()
+        .flatten()
+        .filter_map(|card| card.locale.as_deref())
+        .collect();
+
+    for (card_id, cards) in variants {
+        let primary = primary_variant(cards);
+        let expected = action_signature(primary);
+        for card in cards {
+            if std::ptr::eq(*card, primary) || action_signature(card) == expected {
+                continue;
+            }
+            let message = format!(
+                "localized variant {} of card {} in flow {} has different actions than {}",
+                card.rel_path, card_id, flow_name, primary.rel_path
+            );
+            policy.report(
+                warning(WarningKind::Inconsistent, message)
+                    .at(card.source.root())
+                    .for_card(card),
+                warnings,
+                errors,
+            );
+        }
+
+        let locales: BTreeSet<&str> = cards
+            .iter()
+            .filter_map(|card| card.locale.as_deref())
+            .collect();
+        if locales.is_empty() {
+            continue;
+        }
+        for missing in all_locales.difference(&locales) {
+            let message = format!("card {card_id} in flow {flow_name} has no {missing} variant");
+            policy.report(
+                warning(WarningKind::MissingLocale, message)
+                    .at(primary.source.root())
+                    .for_card(primary),
+                warnings,
+                errors,
+            );
+        }
+    }
+}
+
+/// Route targets and verbs of a card, ignoring titles (which are translated).
+fn action_signature(card: &CardDoc) -> Vec<(&str, Option<&str>)> {
+    let mut signature: Vec<_> = card
+        .actions
+        .iter()
+        .filter_map(|action| Some((action.target.as_ref()?.name(), action.verb.as_deref())))
+        .collect();
+    signature.sort();
+    signature
+}
+
 fn route_key_for_action(
     action: &crate::ir::CardAction,
     target: &RouteTarget,
     index: usize,
 ) -> String {
-    let base = match target {
-        RouteTarget::Step(value) => value.clone(),
-        RouteTarget::CardId(value) => value.clone(),
-    };
+    if let Some(verb) = action.verb.as_ref() {
+        return verb.clone();
+    }
 
+    let base = target.name();
     if !base.is_empty() {
-        return base;
+        return base.to_string();
     }
 
     if let Some(title) = action.title.as_ref()
@@ -0,0 +1,150 @@
+use serde_json::{Value, json};
+
+use crate::cli::GraphFormat;
+use crate::graph::FlowGraph;
+
+pub fn render_graph(graph: &FlowGraph, format: GraphFormat) -> String {
+    match format {
+        GraphFormat::Mermaid => render_mermaid(graph),
+        GraphFormat::Dot => render_dot(graph),
+        GraphFormat::Json => {
+            serde_json::to_string_pretty(&graph_json(graph)).unwrap_or_else(|_| "{}".to_string())
+        }
+    }
+}
+
+pub fn graph_file_extension(format: GraphFormat) -> &'static str {
+    match format {
+        GraphFormat::Mermaid => "mmd",
+        GraphFormat::Dot => "dot",
+        GraphFormat::Json => "json",
+    }
+}
+
+pub fn graph_json(graph: &FlowGraph) -> Value {
+    let entry = graph.entry_node().map(|node| node.name.as_str());
+    let nodes: Vec<Value> = graph
+        .nodes
+        .values()
+        .map(|node| {
+            let mut value = json!({
+                "id": node.name,
+                "card_path": node.card_path,
+                "stub": node.stub,
+                "entry": Some(node.name.as_str()) == entry,
+            });
+            if !node.locales.is_empty() {
+                value["locales"] = json!(node.locales);
+            }
+            value
+        })
+        .collect();
+    let edges: Vec<Value> = graph
+        .nodes
+        .values()
+        .flat_map(|node| {
+            node.routes.iter().map(move |route| {
+                json!({
+                    "from": node.name,
+                    "to": route.target,
+                    "key": route.key,
+                    "verb": route.verb,
+                })
+            })
+        })
+        .collect();
+
+    json!({
+        "flow": graph.flow_name,
+        "entry": entry,
+        "nodes": nodes,
+        "edges": edges,
+    })
+}
+
+fn render_mermaid(graph: &FlowGraph) -> String {
+    let entry = graph.entry_node().map(|node| node.name.as_str());
+    let ids: Vec<&str> = graph.nodes.keys().map(String::as_str).collect();
+    let node_id = |name: &str| {
+        ids.iter()
+            .position(|candidate| *candidate == name)
+            .map(|index| format!("n{index}"))
+            .unwrap_or_else(|| "unknown".to_string())
+    };
+
+    let mut output = String::new();
+    output.push_str(&format!(
+        "---\ntitle: {}\n---\nflowchart TD\n",
+        mermaid_label(&graph.flow_name)
+    ));
+    for node in graph.nodes.values() {
+        let id = node_id(&node.name);
+        let label = mermaid_label(&node.name);
+        if Some(node.name.as_str()) == entry {
+            output.push_str(&format!("  {id}([\"{label} (entry)\"]):::entry\n"));
+        } else if node.stub {
+            output.push_str(&format!("  {id}[\"{label} (stub)\"]:::stub\n"));
+        } else {
+            output.push_str(&format!("  {id}[\"{label}\"]\n"));
+        }
+    }
+    for node in graph.nodes.values() {
+        for route in &node.routes {
+            output.push_str(&format!(
+                "  {} -->|\"{}\"| {}\n",
+                node_id(&node.name),
+                mermaid_label(&route.key),
+                node_id(&route.target)
+            ));
+        }
+    }
+    output.push_str("  classDef entry stroke-width:3px\n");
+    output.push_str("  classDef stub stroke-dasharray:5 5\n");
+    output
+}
+
+fn render_dot(graph: &FlowGraph) -> String {
+    let entry = graph.entry_node().map(|node| node.name.as_str());
+
+    let mut output = String::new();
+    output.push_str(&format!(
+        "digraph \"{}\" {{\n",
+        dot_escape(&graph.flow_name)
+    ));
+    output.push_str("  rankdir=LR;\n");
+    output.push_str("  node [shape=box];\n");
+    for node in graph.nodes.values() {
+        let name = dot_escape(&node.name);
+        if Some(node.name.as_str()) == entry {
+            output.push_str(&format!(
+                "  \"{name}\" [label=\"{name} (entry)\", style=bold, peripheries=2];\n"
+            ));
+        } else if node.stub {
+            output.push_str(&format!(
+                "  \"{name}\" [label=\"{name} (stub)\", style=dashed];\n"
+            ));
+        } else {
+            output.push_str(&format!("  \"{name}\" [label=\"{name}\"];\n"));
+        }
+    }
+    for node in graph.nodes.values() {
+        for route in &node.routes {
+            output.push_str(&format!(
+                "  \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                dot_escape(&node.name),
+                dot_escape(&route.target),
+                dot_escape(&route.key)
+            ));
+        }
+    }
+    output.push_str("}\n");
+    output
+}
+
+fn mermaid_label(value: &str) -> String {
+    value.replace('"', "#quot;")
+}
+
+fn dot_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
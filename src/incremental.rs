@@ -0,0 +1,304 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::card_files::rel_string;
+use crate::config::GenerateOptions;
+use crate::graph::FlowGraph;
+use crate::ir::Warning;
+
+pub const BUILD_STATE_FILE: &str = "build-state.json";
+const BUILD_STATE_VERSION: u32 = 1;
+
+/// What the previous `generate` run produced, persisted in `.cards2pack/build-state.json` so the
+/// next run can skip unchanged work. Any mismatch in version or options starts from scratch.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BuildState {
+    pub version: u32,
+    /// Fingerprint of the tool version and the options that shape the output.
+    pub options: String,
+    /// Content hash of every card and template data source, keyed by path under `--cards`.
+    pub cards: BTreeMap<String, String>,
+    pub flows: BTreeMap<String, FlowState>,
+    /// Set once a pack build succeeded from this workspace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pack: Option<PackState>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlowState {
+    /// [`flow_fingerprint`] of the graph the flow was emitted from.
+    pub fingerprint: String,
+    /// Hash of the `.ygtc` file as written, so hand edits force a re-emit.
+    pub file: String,
+    /// Emission warnings, replayed when the flow is skipped.
+    #[serde(default)]
+    pub warnings: Vec<Warning>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackState {
+    /// [`workspace_fingerprint`] after the last build.
+    pub workspace: String,
+    /// Hash of the `.gtpack` that build produced.
+    pub artifact: String,
+    /// Validation and pack output warnings, replayed when the build is skipped.
+    #[serde(default)]
+    pub warnings: Vec<Warning>,
+}
+
+impl BuildState {
+    /// Loads the previous state, or an empty one when forced, missing, unreadable or built with
+    /// different options.
+    pub fn load(state_dir: &Path, options: &str, force: bool) -> Self {
+        let fresh = BuildState {
+            version: BUILD_STATE_VERSION,
+            options: options.to_string(),
+            ..BuildState::default()
+        };
+        if force {
+            return fresh;
+        }
+        let Ok(contents) = fs::read(state_dir.join(BUILD_STATE_FILE)) else {
+            return fresh;
+        };
+        match serde_json::from_slice::<BuildState>(&contents) {
+            Ok(state) if state.version == BUILD_STATE_VERSION && state.options == options => state,
+            _ => fresh,
+        }
+    }
+
+    pub fn save(&self, state_dir: &Path) -> Result<()> {
+        let path = state_dir.join(BUILD_STATE_FILE);
+        let mut json = serde_json::to_vec_pretty(self).context("serialize build state")?;
+        json.push(b'\n');
+        fs::write(&path, json).with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Source files whose content differs from the previous run (including new files).
+    pub fn changed_cards(&self, current: &BTreeMap<String, String>) -> BTreeSet<String> {
+        current
+            .iter()
+            .filter(|(rel, hash)| self.cards.get(*rel) != Some(*hash))
+            .map(|(rel, _)| rel.clone())
+            .collect()
+    }
+
+    /// The previous emission of `flow_name`, if its fingerprint matches and the file on disk is
+    /// still the one that was written.
+    pub fn unchanged_flow(
+        &self,
+        flow_name: &str,
+        fingerprint: &str,
+        flow_path: &Path,
+    ) -> Option<&FlowState> {
+        let state = self.flows.get(flow_name)?;
+        (state.fingerprint == fingerprint && file_hash(flow_path)? == state.file).then_some(state)
+    }
+
+    /// The previous pack build, if the workspace and the artifact are exactly as it left them.
+    pub fn unchanged_pack(&self, workspace: &str, artifact_path: &Path) -> Option<&PackState> {
+        let state = self.pack.as_ref()?;
+        (state.workspace == workspace && file_hash(artifact_path)? == state.artifact)
+            .then_some(state)
+    }
+}
+
+/// Fingerprint of the options that affect generated files. `out`, `verbose`, `jobs`, `force` and
+/// `max_errors` are left out so a dry run (which generates into a scratch copy) can reuse the real
+/// workspace state.
+pub fn options_fingerprint(options: &GenerateOptions) -> Result<String> {
+    let mut options = options.clone();
+    options.out = PathBuf::new();
+    options.verbose = false;
+    options.jobs = None;
+    options.max_errors = None;
+    options.force = false;
+    let json = serde_json::to_vec(&options).context("serialize generate options")?;
+    let mut hasher = Hasher::new();
+    hasher.field(env!("CARGO_PKG_VERSION").as_bytes());
+    hasher.field(&json);
+    Ok(hasher.finish())
+}
+
+/// Content hashes of `files`, keyed by their `/`-separated path under `cards_dir`.
+pub fn card_hashes(cards_dir: &Path, files: &[PathBuf]) -> Result<BTreeMap<String, String>> {
+    let mut hashes = BTreeMap::new();
+    for path in files {
+        let contents = fs::read(path).with_context(|| format!("read {}", path.display()))?;
+        hashes.insert(rel_string(cards_dir, path), content_hash(&contents));
+    }
+    Ok(hashes)
+}
+
+/// Fingerprint of everything `emit_flow` reads from a graph, plus whether the prompt node is
+/// inserted and whether emission runs strict.
+pub fn flow_fingerprint(graph: &FlowGraph, prompt: bool, strict: bool) -> String {
+    let mut hasher = Hasher::new();
+    hasher.field(env!("CARGO_PKG_VERSION").as_bytes());
+    hasher.field(graph.flow_name.as_bytes());
+    hasher.field(&[u8::from(prompt), u8::from(strict)]);
+    for node in graph.nodes.values() {
+        hasher.field(node.name.as_bytes());
+        hasher.optional(node.card_path.as_deref());
+        hasher.optional(node.data_path.as_deref());
+        hasher.field(&node.locales.len().to_le_bytes());
+        for (locale, path) in &node.locales {
+            hasher.field(locale.as_bytes());
+            hasher.field(path.as_bytes());
+        }
+        hasher.field(&[u8::from(node.stub)]);
+        hasher.field(&node.routes.len().to_le_bytes());
+        for route in &node.routes {
+            hasher.field(route.key.as_bytes());
+            hasher.field(route.target.as_bytes());
+            hasher.optional(route.verb.as_deref());
+        }
+    }
+    hasher.finish()
+}
+
+/// Fingerprint of every file in the workspace except `dist/` and `.cards2pack/`: the inputs of a
+/// `greentic-pack build`.
+pub fn workspace_fingerprint(workspace: &Path) -> Result<String> {
+    let mut hasher = Hasher::new();
+    let walker = WalkDir::new(workspace)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() != 1
+                || !matches!(entry.file_name().to_str(), Some("dist" | ".cards2pack"))
+        });
+    for entry in walker {
+        let entry = entry.with_context(|| format!("walk {}", workspace.display()))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let contents =
+            fs::read(entry.path()).with_context(|| format!("read {}", entry.path().display()))?;
+        hasher.field(rel_string(workspace, entry.path()).as_bytes());
+        hasher.field(&contents);
+    }
+    Ok(hasher.finish())
+}
+
+pub fn file_hash(path: &Path) -> Option<String> {
+    fs::read(path).ok().map(|contents| content_hash(&contents))
+}
+
+pub fn content_hash(contents: &[u8]) -> String {
+    let mut hasher = Hasher::new();
+    hasher.write(contents);
+    hasher.finish()
+}
+
+/// 128-bit FNV-1a. Not cryptographic, but stable across platforms and toolchains, which
+/// `std::hash` does not promise.
+struct Hasher(u128);
+
+impl Hasher {
+    const OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    fn new() -> Self {
+        Self(Self::OFFSET)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u128::from(*byte);
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    /// Length-prefixed, so adjacent fields cannot run into each other.
+    fn field(&mut self, bytes: &[u8]) {
+        self.write(&(bytes.len() as u64).to_le_bytes());
+        self.write(bytes);
+    }
+
+    fn optional(&mut self, value: Option<&str>) {
+        match value {
+            Some(value) => {
+                self.write(&[1]);
+                self.field(value.as_bytes());
+            }
+            None => self.write(&[0]),
+        }
+    }
+
+    fn finish(&self) -> String {
+        format!("{:032x}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BuildState, content_hash, flow_fingerprint};
+    use crate::graph::{FlowGraph, FlowNode, RouteEdge};
+    use std::collections::BTreeMap;
+
+    fn graph(target: &str) -> FlowGraph {
+        let mut nodes = BTreeMap::new();
+        nodes.insert(
+            "start".to_string(),
+            FlowNode {
+                name: "start".to_string(),
+                card_path: Some("assets/cards/start.json".to_string()),
+                data_path: None,
+                locales: BTreeMap::new(),
+                routes: vec![RouteEdge {
+                    key: "next".to_string(),
+                    target: target.to_string(),
+                    verb: None,
+                }],
+                stub: false,
+            },
+        );
+        FlowGraph {
+            flow_name: "main".to_string(),
+            nodes,
+            warnings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn fingerprints_follow_graph_changes() {
+        assert_eq!(
+            flow_fingerprint(&graph("end"), false, false),
+            flow_fingerprint(&graph("end"), false, false)
+        );
+        assert_ne!(
+            flow_fingerprint(&graph("end"), false, false),
+            flow_fingerprint(&graph("done"), false, false)
+        );
+        assert_ne!(
+            flow_fingerprint(&graph("end"), false, false),
+            flow_fingerprint(&graph("end"), true, false)
+        );
+        assert_eq!(content_hash(b"").len(), 32);
+        assert_ne!(content_hash(b"ab"), content_hash(b"ba"));
+    }
+
+    #[test]
+    fn reports_new_and_modified_cards() {
+        let state = BuildState {
+            cards: BTreeMap::from([
+                ("a.json".to_string(), "1".to_string()),
+                ("b.json".to_string(), "2".to_string()),
+            ]),
+            ..BuildState::default()
+        };
+        let current = BTreeMap::from([
+            ("a.json".to_string(), "1".to_string()),
+            ("b.json".to_string(), "3".to_string()),
+            ("c.json".to_string(), "4".to_string()),
+        ]);
+        let changed: Vec<String> = state.changed_cards(&current).into_iter().collect();
+        assert_eq!(changed, vec!["b.json", "c.json"]);
+    }
+}
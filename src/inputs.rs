@@ -0,0 +1,253 @@
+use std::collections::BTreeSet;
+
+use serde_json::{Map, Value, json};
+
+use crate::diagnostics::warning;
+use crate::ir::{Warning, WarningKind};
+use crate::source_map::{SourceMap, escape_pointer_token};
+
+const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Builds a JSON Schema for the values a submit from this card produces, keyed by input id.
+/// Returns `None` when the card declares no `Input.*` elements.
+pub fn input_schema(
+    card: &Value,
+    card_id: &str,
+    rel_path: &str,
+    source: &SourceMap,
+    warnings: &mut Vec<Warning>,
+) -> Option<Value> {
+    let mut inputs = Vec::new();
+    collect_inputs(card, String::new(), &mut inputs);
+    if inputs.is_empty() {
+        return None;
+    }
+
+    let mut properties = Map::new();
+    let mut required = BTreeSet::new();
+    for (pointer, input) in inputs {
+        let input_type = input
+            .get("type")
+            .and_then(|value| value.as_str())
+            .unwrap_or_default();
+        let Some(id) = input.get("id").and_then(|value| value.as_str()) else {
+            warnings.push(
+                warning(
+                    WarningKind::Inconsistent,
+                    format!("{input_type} without id in {rel_path} is not submitted"),
+                )
+                .at(source.locate(&pointer))
+                .card_id(card_id)
+                .rel_path(rel_path),
+            );
+            continue;
+        };
+        if properties.contains_key(id) {
+            warnings.push(
+                warning(
+                    WarningKind::Inconsistent,
+                    format!("duplicate input id {id} in {rel_path}"),
+                )
+                .at(source.locate(&format!("{pointer}/id")))
+                .card_id(card_id)
+                .rel_path(rel_path),
+            );
+            continue;
+        }
+        if input.get("isRequired").and_then(|value| value.as_bool()) == Some(true) {
+            required.insert(id.to_string());
+        }
+        properties.insert(id.to_string(), property_schema(input_type, input));
+    }
+
+    Some(json!({
+        "$schema": SCHEMA_DIALECT,
+        "title": card_id,
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    }))
+}
+
+/// Collects `Input.*` elements with their JSON pointers.
+fn collect_inputs<'a>(
+    value: &'a Value,
+    path: String,
+    inputs: &mut Vec<(String, &'a Map<String, Value>)>,
+) {
+    match value {
+        Value::Object(map) => {
+            let is_input = map
+                .get("type")
+                .and_then(|value| value.as_str())
+                .is_some_and(|kind| kind.starts_with("Input."));
+            if is_input {
+                inputs.push((path.clone(), map));
+            }
+            for (key, child) in map {
+                if key != "data" {
+                    collect_inputs(
+                        child,
+                        format!("{path}/{}", escape_pointer_token(key)),
+                        inputs,
+                    );
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                collect_inputs(item, format!("{path}/{index}"), inputs);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn property_schema(input_type: &str, input: &Map<String, Value>) -> Value {
+    let mut schema = Map::new();
+    let mut set = |key: &str, value: Option<&Value>| {
+        if let Some(value) = value {
+            schema.insert(key.to_string(), value.clone());
+        }
+    };
+
+    match input_type {
+        "Input.Text" => {
+            set("type", Some(&json!("string")));
+            set("maxLength", input.get("maxLength"));
+            set("pattern", input.get("regex"));
+            match input.get("style").and_then(|value| value.as_str()) {
+                Some("Email") | Some("email") => set("format", Some(&json!("email"))),
+                Some("Url") | Some("url") => set("format", Some(&json!("uri"))),
+                _ => {}
+            }
+        }
+        "Input.Number" => {
+            set("type", Some(&json!("number")));
+            set("minimum", input.get("min"));
+            set("maximum", input.get("max"));
+        }
+        "Input.Date" | "Input.Time" => {
+            let format = if input_type == "Input.Date" {
+                "date"
+            } else {
+                "time"
+            };
+            set("type", Some(&json!("string")));
+            set("format", Some(&json!(format)));
+            set("formatMinimum", input.get("min"));
+            set("formatMaximum", input.get("max"));
+        }
+        "Input.Toggle" => {
+            let on = input.get("valueOn").cloned().unwrap_or(json!("true"));
+            let off = input.get("valueOff").cloned().unwrap_or(json!("false"));
+            set("type", Some(&json!("string")));
+            set("enum", Some(&json!([on, off])));
+        }
+        "Input.ChoiceSet" => {
+            let choices: Vec<String> = input
+                .get("choices")
+                .and_then(|value| value.as_array())
+                .map(|choices| {
+                    choices
+                        .iter()
+                        .filter_map(|choice| choice.get("value")?.as_str())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default();
+            set("type", Some(&json!("string")));
+            let multi = input.get("isMultiSelect").and_then(|value| value.as_bool()) == Some(true);
+            if multi && !choices.is_empty() {
+                // Multi-select submits the chosen values comma-separated.
+                let alternatives = choices
+                    .iter()
+                    .map(|choice| escape_regex(choice))
+                    .collect::<Vec<_>>()
+                    .join("|");
+                set(
+                    "pattern",
+                    Some(&json!(format!("^({alternatives})(,({alternatives}))*$"))),
+                );
+            } else if !choices.is_empty() {
+                set("enum", Some(&json!(choices)));
+            }
+        }
+        "Input.Rating" => {
+            set("type", Some(&json!("number")));
+            set("minimum", Some(&json!(0)));
+            set("maximum", input.get("max"));
+        }
+        _ => {}
+    }
+
+    set("title", input.get("label"));
+    set("default", input.get("value"));
+    schema.insert("x-input-type".to_string(), json!(input_type));
+    Value::Object(schema)
+}
+
+fn escape_regex(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for current in value.chars() {
+        if "\\.+*?()|[]{}^$".contains(current) {
+            escaped.push('\\');
+        }
+        escaped.push(current);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::input_schema;
+    use crate::source_map::SourceMap;
+    use serde_json::json;
+
+    #[test]
+    fn builds_schema_from_nested_inputs() {
+        let card = json!({
+            "type": "AdaptiveCard",
+            "body": [
+                { "type": "Input.Text", "id": "email", "style": "Email", "isRequired": true, "label": "Email" },
+                { "type": "Container", "items": [
+                    { "type": "Input.Number", "id": "age", "min": 18, "max": 99 },
+                    { "type": "Input.ChoiceSet", "id": "team", "choices": [
+                        { "title": "Ops", "value": "ops" }, { "title": "R&D", "value": "r.d" }
+                    ], "isMultiSelect": true }
+                ]},
+                { "type": "Input.Toggle", "id": "agree", "valueOn": "yes", "valueOff": "no", "isRequired": true },
+                { "type": "Input.Text", "id": "email" },
+                { "type": "Input.Date" }
+            ]
+        });
+        let mut warnings = Vec::new();
+        let source = SourceMap::file_only("cards/signup.json");
+        let schema = input_schema(&card, "signup", "signup.json", &source, &mut warnings).unwrap();
+
+        assert_eq!(schema["title"], "signup");
+        assert_eq!(schema["required"], json!(["agree", "email"]));
+        assert_eq!(schema["properties"]["email"]["format"], "email");
+        assert_eq!(schema["properties"]["email"]["title"], "Email");
+        assert_eq!(schema["properties"]["age"]["minimum"], 18);
+        assert_eq!(schema["properties"]["age"]["maximum"], 99);
+        assert_eq!(
+            schema["properties"]["team"]["pattern"],
+            "^(ops|r\\.d)(,(ops|r\\.d))*$"
+        );
+        assert_eq!(schema["properties"]["agree"]["enum"], json!(["yes", "no"]));
+        assert_eq!(warnings.len(), 2);
+        let pointers: Vec<_> = warnings
+            .iter()
+            .map(|warning| warning.location.as_ref().unwrap().pointer.as_deref())
+            .collect();
+        assert_eq!(pointers, vec![Some("/body/3/id"), Some("/body/4")]);
+    }
+
+    #[test]
+    fn returns_none_without_inputs() {
+        let card = json!({ "type": "AdaptiveCard", "body": [{ "type": "TextBlock" }] });
+        let source = SourceMap::file_only("cards/info.json");
+        assert!(input_schema(&card, "info", "info.json", &source, &mut Vec::new()).is_none());
+    }
+}
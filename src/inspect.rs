@@ -0,0 +1,119 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+
+use crate::cli::{GraphArgs, GraphFormat, OutputFormat, ScanArgs};
+use crate::config::resolve_scan;
+use crate::diagnostics::{StrictErrors, summarize_scan};
+use crate::graph::{FlowGraph, build_flow_graph_collecting};
+use crate::graph_export::{graph_file_extension, graph_json, render_graph};
+use crate::ir::Manifest;
+use crate::scan::{ScanConfig, scan_cards_collecting};
+use crate::workspace::write_sarif_for;
+
+pub fn scan(args: &ScanArgs) -> Result<()> {
+    let config = resolve_scan(&args.scan)?;
+    let result = scan_with_graphs(&config);
+    if let Some(path) = args.sarif.as_deref() {
+        write_sarif_for(path, result.as_ref().map(|(manifest, _)| manifest))?;
+    }
+    let (manifest, _) = result?;
+
+    match args.format {
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(&manifest).context("serialize manifest")?;
+            println!("{json}");
+        }
+        OutputFormat::Text => println!("{}", summarize_scan(&manifest)),
+    }
+
+    Ok(())
+}
+
+pub fn graph(args: &GraphArgs) -> Result<()> {
+    let config = resolve_scan(&args.scan)?;
+    let (manifest, mut graphs) = scan_with_graphs(&config)?;
+    for warning in manifest.warnings.iter().filter(|w| !w.is_allowed()) {
+        eprintln!("warning: {warning}");
+    }
+
+    if let Some(flow) = args.flow.as_deref() {
+        graphs.retain(|graph| graph.flow_name == flow);
+        if graphs.is_empty() {
+            bail!("flow {flow} not found in {}", config.cards_dir.display());
+        }
+    }
+
+    if let Some(out) = args.out.as_deref() {
+        for path in write_graphs(&graphs, out, args.format)? {
+            println!("{}", path.display());
+        }
+        return Ok(());
+    }
+
+    match args.format {
+        GraphFormat::Json => {
+            let flows: Vec<_> = graphs.iter().map(graph_json).collect();
+            let json = serde_json::to_string_pretty(&flows).context("serialize flow graphs")?;
+            println!("{json}");
+        }
+        format => {
+            let rendered: Vec<String> = graphs
+                .iter()
+                .map(|graph| render_graph(graph, format))
+                .collect();
+            print!("{}", rendered.join("\n"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Scans the cards directory and builds a graph per flow, folding graph warnings into the manifest.
+/// Strict mode fails only after both phases, with every error they found.
+pub fn scan_with_graphs(config: &ScanConfig) -> Result<(Manifest, Vec<FlowGraph>)> {
+    if !config.cards_dir.is_dir() {
+        bail!(
+            "cards directory does not exist: {}",
+            config.cards_dir.display()
+        );
+    }
+
+    let mut errors = StrictErrors::new(config.max_errors);
+    let mut manifest = scan_cards_collecting(config, &mut errors)?;
+    let mut graphs = Vec::new();
+    for flow in &manifest.flows {
+        let graph = build_flow_graph_collecting(flow, config.policy(), &mut errors)?;
+        manifest.warnings.extend(graph.warnings.iter().cloned());
+        graphs.push(graph);
+    }
+    errors.finish_with(&manifest.warnings)?;
+    manifest.diagnostics.warnings_count =
+        manifest.warnings.iter().filter(|w| !w.is_allowed()).count();
+
+    Ok((manifest, graphs))
+}
+
+pub fn write_graphs(
+    graphs: &[FlowGraph],
+    out_dir: &Path,
+    format: GraphFormat,
+) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(out_dir)
+        .with_context(|| format!("failed to create {}", out_dir.display()))?;
+
+    let mut paths = Vec::new();
+    for graph in graphs {
+        let path = out_dir.join(format!(
+            "{}.{}",
+            graph.flow_name,
+            graph_file_extension(format)
+        ));
+        fs::write(&path, render_graph(graph, format))
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
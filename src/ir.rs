@@ -1,77 +1,382 @@
+use std::fmt;
 use std::path::PathBuf;
 
-use serde::Serialize;
+use clap::ValueEnum;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::cli::GroupBy;
+use crate::cli::{GroupBy, HostProfile};
+use crate::config::GenerateOptions;
+use crate::scan::RoutingKeys;
+use crate::source_map::SourceMap;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct CardDoc {
+    /// Source path under `--cards` (may be `.yaml`, `.yml` or `.jsonc`).
     pub rel_path: String,
+    /// Normalised JSON asset path under `assets/cards`.
+    pub asset_path: String,
+    /// Sample data for a templated card (`<stem>.data.json`), as a source path under `--cards`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_path: Option<String>,
+    /// Packaged data asset path under `assets/cards`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_asset_path: Option<String>,
+    /// JSON Schema of the values a submit produces, built from the card's `Input.*` elements.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_schema: Option<Value>,
+    /// Asset path under `assets/cards` where `input_schema` is written (`<stem>.schema.json`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_asset_path: Option<String>,
     pub abs_path: PathBuf,
     pub card_id: String,
+    /// Locale of a localized variant, from `greentic.locale` or a `<card>.<locale>.json` name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// Set when `card_id` is the file stem with its locale suffix removed.
+    #[serde(skip)]
+    pub id_from_locale_suffix: bool,
     pub flow_name: String,
     pub actions: Vec<CardAction>,
+    /// Positions in the source file, for warning locations.
+    #[serde(skip)]
+    pub source: SourceMap,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct CardAction {
+    /// JSON pointer to the action inside the card, e.g. `/body/2/actions/0`.
+    pub path: String,
     pub action_type: String,
     pub title: Option<String>,
+    /// `Action.Execute` verb; used as the route key and, without another target, as the target.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verb: Option<String>,
     pub target: Option<RouteTarget>,
     pub data: Value,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub enum RouteTarget {
     Step(String),
     CardId(String),
+    Verb(String),
 }
 
-#[derive(Debug, Serialize)]
+impl RouteTarget {
+    /// Node name the target resolves to in the flow graph.
+    pub fn name(&self) -> &str {
+        match self {
+            RouteTarget::Step(name) | RouteTarget::CardId(name) | RouteTarget::Verb(name) => name,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct FlowGroup {
     pub flow_name: String,
     pub cards: Vec<CardDoc>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct InputInfo {
     pub cards_dir: PathBuf,
     pub group_by: Option<GroupBy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flow_field: Option<String>,
     pub default_flow: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub routing_keys: Option<RoutingKeys>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<HostProfile>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Manifest {
+    /// Format version; older manifests are migrated by `manifest::read_manifest`.
     pub version: u8,
     pub generated_at: String,
     pub input: InputInfo,
     pub flows: Vec<FlowGroup>,
     pub warnings: Vec<Warning>,
     pub diagnostics: Diagnostics,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<GenerateOptions>,
+}
+
+/// Result document printed by `generate --format json`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GenerateReport {
+    pub manifest_path: PathBuf,
+    pub diagnostics: Diagnostics,
+    pub warnings: Vec<Warning>,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct Warning {
     pub kind: WarningKind,
     pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+    /// Effective severity after `--allow`/`--warn`/`--deny`; denied warnings fail the run, so a
+    /// written manifest holds `warn` and `allow`.
+    #[serde(default)]
+    pub severity: Severity,
+    /// Card the warning concerns.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub card_id: Option<String>,
+    /// Flow of that card, or the flow being built or emitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flow: Option<String>,
+    /// Card or data file path relative to the cards directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rel_path: Option<String>,
+    /// Route target (step or card id) involved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Route key in the emitted flow.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub route_key: Option<String>,
+    /// External tool command the warning comes from, e.g. `greentic-pack doctor`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+}
+
+impl Warning {
+    /// Allowed warnings stay in the manifest but are left out of summaries and counts.
+    pub fn is_allowed(&self) -> bool {
+        self.severity == Severity::Allow
+    }
+
+    /// Attaches the source location the warning is about.
+    pub fn at(mut self, location: Location) -> Self {
+        self.location = Some(location);
+        self
+    }
+
+    /// Sets `card_id`, `flow` and `rel_path` from the card.
+    pub fn for_card(self, card: &CardDoc) -> Self {
+        self.card_id(&card.card_id)
+            .flow(&card.flow_name)
+            .rel_path(&card.rel_path)
+    }
+
+    pub fn card_id(mut self, card_id: impl Into<String>) -> Self {
+        self.card_id = Some(card_id.into());
+        self
+    }
+
+    pub fn flow(mut self, flow: impl Into<String>) -> Self {
+        self.flow = Some(flow.into());
+        self
+    }
+
+    pub fn rel_path(mut self, rel_path: impl Into<String>) -> Self {
+        self.rel_path = Some(rel_path.into());
+        self
+    }
+
+    pub fn target(mut self, target: impl Into<String>) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    pub fn route_key(mut self, route_key: impl Into<String>) -> Self {
+        self.route_key = Some(route_key.into());
+        self
+    }
+
+    pub fn tool(mut self, tool: impl Into<String>) -> Self {
+        self.tool = Some(tool.into());
+        self
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "{location}: {}", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Where a warning points: a file and, when known, the line, column and JSON pointer of the
+/// element involved.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema)]
+pub struct Location {
+    /// Source path as given on the command line, e.g. `cards/welcome.json`.
+    pub file: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pointer: Option<String>,
+}
+
+/// Renders as `path:line:col` (or as much of it as is known) so editors can jump to it.
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.file)?;
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+            if let Some(column) = self.column {
+                write!(f, ":{column}")?;
+            }
+        }
+        Ok(())
+    }
 }
 
-#[derive(Debug, Serialize, Clone)]
+/// Warning categories. `--allow`/`--warn`/`--deny` accept the snake_case name or the code.
+#[derive(
+    Debug,
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    ValueEnum,
+    JsonSchema,
+)]
 #[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
 pub enum WarningKind {
+    #[serde(alias = "C2P001")]
+    #[value(alias = "C2P001")]
     Inconsistent,
+    #[serde(alias = "C2P002")]
+    #[value(alias = "C2P002")]
     MissingTarget,
+    #[serde(alias = "C2P003")]
+    #[value(alias = "C2P003")]
     MissingFlow,
+    #[serde(alias = "C2P004")]
+    #[value(alias = "C2P004")]
     MissingCardId,
+    #[serde(alias = "C2P005")]
+    #[value(alias = "C2P005")]
     DuplicateCardId,
+    #[serde(alias = "C2P006")]
+    #[value(alias = "C2P006")]
     InvalidJson,
+    #[serde(alias = "C2P007")]
+    #[value(alias = "C2P007")]
     IgnoredFile,
+    #[serde(alias = "C2P008")]
+    #[value(alias = "C2P008")]
     PackOutput,
+    #[serde(alias = "C2P009")]
+    #[value(alias = "C2P009")]
     Validation,
+    #[serde(alias = "C2P010")]
+    #[value(alias = "C2P010")]
+    UnboundVariable,
+    #[serde(alias = "C2P011")]
+    #[value(alias = "C2P011")]
+    Compatibility,
+    #[serde(alias = "C2P012")]
+    #[value(alias = "C2P012")]
+    MissingLocale,
+    #[serde(alias = "C2P013")]
+    #[value(alias = "C2P013")]
+    NoRoutes,
+}
+
+impl WarningKind {
+    pub const ALL: [WarningKind; 13] = [
+        WarningKind::Inconsistent,
+        WarningKind::MissingTarget,
+        WarningKind::MissingFlow,
+        WarningKind::MissingCardId,
+        WarningKind::DuplicateCardId,
+        WarningKind::InvalidJson,
+        WarningKind::IgnoredFile,
+        WarningKind::PackOutput,
+        WarningKind::Validation,
+        WarningKind::UnboundVariable,
+        WarningKind::Compatibility,
+        WarningKind::MissingLocale,
+        WarningKind::NoRoutes,
+    ];
+
+    /// Name used in output, config files and `--allow`/`--warn`/`--deny`.
+    pub fn name(self) -> &'static str {
+        match self {
+            WarningKind::Inconsistent => "inconsistent",
+            WarningKind::MissingTarget => "missing_target",
+            WarningKind::MissingFlow => "missing_flow",
+            WarningKind::MissingCardId => "missing_card_id",
+            WarningKind::DuplicateCardId => "duplicate_card_id",
+            WarningKind::InvalidJson => "invalid_json",
+            WarningKind::IgnoredFile => "ignored_file",
+            WarningKind::PackOutput => "pack_output",
+            WarningKind::Validation => "validation",
+            WarningKind::UnboundVariable => "unbound_variable",
+            WarningKind::Compatibility => "compatibility",
+            WarningKind::MissingLocale => "missing_locale",
+            WarningKind::NoRoutes => "no_routes",
+        }
+    }
+
+    /// Stable code; codes are never renumbered or reused.
+    pub fn code(self) -> &'static str {
+        match self {
+            WarningKind::Inconsistent => "C2P001",
+            WarningKind::MissingTarget => "C2P002",
+            WarningKind::MissingFlow => "C2P003",
+            WarningKind::MissingCardId => "C2P004",
+            WarningKind::DuplicateCardId => "C2P005",
+            WarningKind::InvalidJson => "C2P006",
+            WarningKind::IgnoredFile => "C2P007",
+            WarningKind::PackOutput => "C2P008",
+            WarningKind::Validation => "C2P009",
+            WarningKind::UnboundVariable => "C2P010",
+            WarningKind::Compatibility => "C2P011",
+            WarningKind::MissingLocale => "C2P012",
+            WarningKind::NoRoutes => "C2P013",
+        }
+    }
+
+    /// Severity without overrides. No kind denies by default, so only `--strict` (for the checks it
+    /// covers) and `--deny` escalate; see the README for why. Kinds that report expected layouts
+    /// (final cards, files deliberately left out of the pack) are allowed.
+    pub fn default_severity(self) -> Severity {
+        match self {
+            WarningKind::IgnoredFile | WarningKind::NoRoutes => Severity::Allow,
+            WarningKind::Inconsistent
+            | WarningKind::MissingTarget
+            | WarningKind::MissingFlow
+            | WarningKind::MissingCardId
+            | WarningKind::DuplicateCardId
+            | WarningKind::InvalidJson
+            | WarningKind::PackOutput
+            | WarningKind::Validation
+            | WarningKind::UnboundVariable
+            | WarningKind::Compatibility
+            | WarningKind::MissingLocale => Severity::Warn,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Recorded in the manifest but not printed.
+    Allow,
+    #[default]
+    Warn,
+    /// Fails the run.
+    Deny,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct Diagnostics {
     pub workspace_root: PathBuf,
     pub dist_artifact: Option<PathBuf>,
@@ -81,7 +386,7 @@ pub struct Diagnostics {
     pub warnings_count: usize,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct FlowSummary {
     pub flow_name: String,
     pub card_count: usize,
@@ -1,11 +1,27 @@
+pub mod card_files;
+pub mod card_source;
 pub mod cli;
+pub mod compat;
+pub mod config;
 pub mod diagnostics;
 pub mod emit_flow;
 pub mod graph;
+pub mod graph_export;
+pub mod incremental;
+pub mod inputs;
+pub mod inspect;
 pub mod ir;
+pub mod locale;
+pub mod manifest;
+pub mod parallel;
+pub mod plan;
 pub mod qa_integration;
+pub mod sarif;
 pub mod scan;
+pub mod source_map;
+pub mod template;
 pub mod tools;
+pub mod watch;
 pub mod workspace;
 
 use anyhow::Result;
@@ -14,5 +30,7 @@ use cli::{Cli, Commands};
 pub fn run(cli: Cli) -> Result<()> {
     match cli.command {
         Commands::Generate(args) => workspace::generate(&args),
+        Commands::Scan(args) => inspect::scan(&args),
+        Commands::Graph(args) => inspect::graph(&args),
     }
 }
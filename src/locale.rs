@@ -0,0 +1,86 @@
+use serde_json::Value;
+
+/// Card field that declares a variant's locale explicitly.
+pub const LOCALE_FIELD: &str = "/greentic/locale";
+
+/// Splits a locale suffix off a file stem: `welcome.en` -> (`welcome`, `en`),
+/// `welcome.pt_BR` -> (`welcome`, `pt-BR`). Stems without an ISO 639-1 suffix return `None`.
+pub fn split_locale_suffix(stem: &str) -> Option<(&str, String)> {
+    let (base, suffix) = stem.rsplit_once('.')?;
+    if base.is_empty() || !is_locale_tag(suffix) {
+        return None;
+    }
+    Some((base, normalize(suffix)))
+}
+
+/// Locale declared in the card's `greentic.locale` field.
+pub fn declared_locale(card: &Value) -> Option<String> {
+    card.pointer(LOCALE_FIELD)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(normalize)
+}
+
+/// An ISO 639-1 language with an optional region or script (`en`, `en-GB`, `zh-Hant`, `es-419`).
+fn is_locale_tag(value: &str) -> bool {
+    let mut parts = value.split(['-', '_']);
+    let language = parts.next().unwrap_or_default();
+    let subtag_ok = match parts.next() {
+        None => true,
+        Some(subtag) => {
+            (subtag.len() == 2 && subtag.chars().all(|c| c.is_ascii_alphabetic()))
+                || (subtag.len() == 4 && subtag.chars().all(|c| c.is_ascii_alphabetic()))
+                || (subtag.len() == 3 && subtag.chars().all(|c| c.is_ascii_digit()))
+        }
+    };
+    ISO_639_1.contains(&language) && subtag_ok && parts.next().is_none()
+}
+
+/// ISO 639-1 language codes.
+const ISO_639_1: [&str; 183] = [
+    "aa", "ab", "ae", "af", "ak", "am", "an", "ar", "as", "av", "ay", "az", "ba", "be", "bg", "bi",
+    "bm", "bn", "bo", "br", "bs", "ca", "ce", "ch", "co", "cr", "cs", "cu", "cv", "cy", "da", "de",
+    "dv", "dz", "ee", "el", "en", "eo", "es", "et", "eu", "fa", "ff", "fi", "fj", "fo", "fr", "fy",
+    "ga", "gd", "gl", "gn", "gu", "gv", "ha", "he", "hi", "ho", "hr", "ht", "hu", "hy", "hz", "ia",
+    "id", "ie", "ig", "ii", "ik", "io", "is", "it", "iu", "ja", "jv", "ka", "kg", "ki", "kj", "kk",
+    "kl", "km", "kn", "ko", "kr", "ks", "ku", "kv", "kw", "ky", "la", "lb", "lg", "li", "ln", "lo",
+    "lt", "lu", "lv", "mg", "mh", "mi", "mk", "ml", "mn", "mr", "ms", "mt", "my", "na", "nb", "nd",
+    "ne", "ng", "nl", "nn", "no", "nr", "nv", "ny", "oc", "oj", "om", "or", "os", "pa", "pi", "pl",
+    "ps", "pt", "qu", "rm", "rn", "ro", "ru", "rw", "sa", "sc", "sd", "se", "sg", "si", "sk", "sl",
+    "sm", "sn", "so", "sq", "sr", "ss", "st", "su", "sv", "sw", "ta", "te", "tg", "th", "ti", "tk",
+    "tl", "tn", "to", "tr", "ts", "tt", "tw", "ty", "ug", "uk", "ur", "uz", "ve", "vi", "vo", "wa",
+    "wo", "xh", "yi", "yo", "za", "zh", "zu",
+];
+
+fn normalize(value: &str) -> String {
+    value.replace('_', "-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_locale_suffix;
+
+    #[test]
+    fn recognises_locale_suffixes() {
+        assert_eq!(
+            split_locale_suffix("welcome.en"),
+            Some(("welcome", "en".to_string()))
+        );
+        assert_eq!(
+            split_locale_suffix("hr.welcome.pt_BR"),
+            Some(("hr.welcome", "pt-BR".to_string()))
+        );
+        assert_eq!(
+            split_locale_suffix("welcome.es-419"),
+            Some(("welcome", "es-419".to_string()))
+        );
+        assert_eq!(split_locale_suffix("welcome"), None);
+        assert_eq!(split_locale_suffix("welcome.v2"), None);
+        assert_eq!(split_locale_suffix("welcome.EN"), None);
+        assert_eq!(split_locale_suffix("welcome.draft"), None);
+        assert_eq!(split_locale_suffix(".en"), None);
+        assert_eq!(split_locale_suffix("approve.ok"), None);
+        assert_eq!(split_locale_suffix("welcome.xx-GB"), None);
+    }
+}
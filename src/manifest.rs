@@ -0,0 +1,136 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde_json::{Map, Value, json};
+
+use crate::card_source::asset_rel_path;
+use crate::ir::Manifest;
+use crate::scan::RoutingKeys;
+
+/// Version written to `.cards2pack/manifest.json`. Bump it, and add a step to [`migrate`], when a
+/// change would stop an older manifest from deserializing or would change what a field means.
+pub const MANIFEST_VERSION: u8 = 2;
+
+/// JSON Schema of the current manifest version; `schema/manifest.schema.json` is generated from it.
+pub fn manifest_schema() -> Value {
+    let mut schema =
+        serde_json::to_value(schemars::schema_for!(Manifest)).expect("manifest schema serializes");
+    schema["title"] = json!(format!("cards2pack manifest v{MANIFEST_VERSION}"));
+    schema["properties"]["version"]["const"] = json!(MANIFEST_VERSION);
+    schema
+}
+
+/// Reads a manifest written by any released version, migrating it to [`MANIFEST_VERSION`].
+pub fn read_manifest(path: &Path) -> Result<Manifest> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    parse_manifest(&contents).with_context(|| format!("invalid manifest {}", path.display()))
+}
+
+/// Parses manifest JSON, migrating it to [`MANIFEST_VERSION`] first.
+pub fn parse_manifest(contents: &str) -> Result<Manifest> {
+    let value: Value = serde_json::from_str(contents).context("manifest is not JSON")?;
+    let value = migrate(value)?;
+    serde_json::from_value(value).context("manifest does not match the current schema")
+}
+
+/// Upgrades a manifest document one version at a time until it is current.
+pub fn migrate(mut manifest: Value) -> Result<Value> {
+    let version = manifest
+        .get("version")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| anyhow!("manifest has no numeric version"))?;
+    if version == 0 || version > u64::from(MANIFEST_VERSION) {
+        bail!("unsupported manifest version {version} (this build reads 1 to {MANIFEST_VERSION})");
+    }
+
+    for from in version..u64::from(MANIFEST_VERSION) {
+        match from {
+            1 => v1_to_v2(&mut manifest),
+            _ => unreachable!("no migration from manifest version {from}"),
+        }
+        manifest["version"] = json!(from + 1);
+    }
+    Ok(manifest)
+}
+
+/// Version 1 grew field by field without a schema; older writers omit what later ones require.
+fn v1_to_v2(manifest: &mut Value) {
+    for card in array_mut(manifest, "flows")
+        .flat_map(|flow| array_mut(flow, "cards"))
+        .filter_map(Value::as_object_mut)
+    {
+        if !card.contains_key("asset_path")
+            && let Some(rel_path) = card.get("rel_path").and_then(Value::as_str)
+        {
+            let asset = asset_rel_path(Path::new(rel_path))
+                .to_string_lossy()
+                .replace('\\', "/");
+            card.insert("asset_path".to_string(), json!(asset));
+        }
+        // Before nested action discovery only the top-level `actions` array was read.
+        if let Some(Value::Array(actions)) = card.get_mut("actions") {
+            for (index, action) in actions.iter_mut().enumerate() {
+                if let Some(action) = action.as_object_mut() {
+                    action
+                        .entry("path")
+                        .or_insert_with(|| json!(format!("/actions/{index}")));
+                }
+            }
+        }
+    }
+
+    for warning in array_mut(manifest, "warnings").filter_map(Value::as_object_mut) {
+        warning.entry("severity").or_insert_with(|| json!("warn"));
+    }
+
+    if let Some(input) = manifest.get_mut("input").and_then(Value::as_object_mut) {
+        kebab_case_group_by(input);
+    }
+
+    if let Some(config) = manifest.get_mut("config").and_then(Value::as_object_mut) {
+        kebab_case_group_by(config);
+        fill_defaults(
+            config,
+            &[
+                ("strict", json!(false)),
+                ("routing_keys", json!(RoutingKeys::default())),
+                ("include", json!([])),
+                ("exclude", json!([])),
+                ("verbose", json!(false)),
+                ("prompt", json!(false)),
+                ("expand_templates", json!(false)),
+            ],
+        );
+    }
+}
+
+/// `GroupBy` was first written with its variant names (`"Folder"`, `"FlowField"`).
+fn kebab_case_group_by(object: &mut Map<String, Value>) {
+    let Some(group_by) = object.get_mut("group_by") else {
+        return;
+    };
+    let renamed = match group_by.as_str() {
+        Some("Folder") => "folder",
+        Some("FlowField") => "flow-field",
+        _ => return,
+    };
+    *group_by = json!(renamed);
+}
+
+fn array_mut<'a>(value: &'a mut Value, key: &str) -> impl Iterator<Item = &'a mut Value> {
+    value
+        .get_mut(key)
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+}
+
+fn fill_defaults(object: &mut Map<String, Value>, defaults: &[(&str, Value)]) {
+    for (key, value) in defaults {
+        object
+            .entry(key.to_string())
+            .or_insert_with(|| value.clone());
+    }
+}
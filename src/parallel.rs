@@ -0,0 +1,78 @@
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Worker count for `--jobs`: the requested value, or the available parallelism when unset or 0.
+pub fn worker_count(jobs: Option<usize>) -> usize {
+    match jobs {
+        Some(jobs) if jobs > 0 => jobs,
+        _ => thread::available_parallelism().map_or(1, NonZeroUsize::get),
+    }
+}
+
+/// Applies `f` to every item on up to `jobs` scoped threads and returns the results in input
+/// order, so callers stay deterministic whatever the scheduling.
+pub fn map_ordered<T, R, F>(items: &[T], jobs: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let workers = jobs.clamp(1, items.len().max(1));
+    if workers == 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<(usize, R)>> = Mutex::new(Vec::with_capacity(items.len()));
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(item) = items.get(index) else {
+                        break;
+                    };
+                    let result = f(item);
+                    results
+                        .lock()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner())
+                        .push((index, result));
+                }
+            });
+        }
+    });
+
+    let mut results = results
+        .into_inner()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{map_ordered, worker_count};
+
+    #[test]
+    fn keeps_input_order() {
+        let items: Vec<u64> = (0..200).collect();
+        let squares = map_ordered(&items, 8, |value| {
+            std::thread::sleep(std::time::Duration::from_micros(200 - value));
+            value * value
+        });
+        assert_eq!(
+            squares,
+            items.iter().map(|value| value * value).collect::<Vec<_>>()
+        );
+        assert!(map_ordered(&[] as &[u64], 4, |value| *value).is_empty());
+    }
+
+    #[test]
+    fn zero_jobs_means_available_parallelism() {
+        assert_eq!(worker_count(Some(3)), 3);
+        assert!(worker_count(Some(0)) >= 1);
+        assert_eq!(worker_count(None), worker_count(Some(0)));
+    }
+}
@@ -0,0 +1,460 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+use walkdir::WalkDir;
+
+use crate::cli::OutputFormat;
+use crate::config::GenerateOptions;
+use crate::ir::{Diagnostics, Warning};
+use crate::workspace::generate_workspace_for;
+
+const DIFF_CONTEXT: usize = 3;
+const MANIFEST_PATH: &str = ".cards2pack/manifest.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Create,
+    Modify,
+    Delete,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileChange {
+    pub path: String,
+    pub kind: ChangeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+/// Result document printed by `generate --dry-run --format json`.
+#[derive(Debug, Serialize)]
+pub struct PlanReport<'a> {
+    /// The `--out` the plan is for; nothing under it was written.
+    pub out: &'a Path,
+    pub changes: &'a [FileChange],
+    pub diagnostics: &'a Diagnostics,
+    pub warnings: &'a [Warning],
+}
+
+/// Runs generate against a temporary copy of `--out` and prints what would change, as text or as
+/// one JSON document.
+pub fn dry_run(options: &GenerateOptions, format: OutputFormat) -> Result<()> {
+    let scratch = tempfile::Builder::new()
+        .prefix("cards2pack-plan-")
+        .tempdir()
+        .context("failed to create dry-run directory")?;
+    let planned_out = scratch.path().join("workspace");
+    if options.out.is_dir() {
+        copy_tree(&options.out, &planned_out)?;
+    }
+
+    let mut planned = options.clone();
+    planned.out = planned_out.clone();
+    let manifest = generate_workspace_for(&planned, false, &options.out)?;
+
+    let changes = diff_trees(&options.out, &planned_out)?;
+    match format {
+        OutputFormat::Text => print_plan(
+            &options.out,
+            &changes,
+            &manifest.warnings,
+            manifest.diagnostics.warnings_count,
+        ),
+        OutputFormat::Json => {
+            // Diagnostics describe the scratch copy; report them for the real `--out`.
+            let mut diagnostics = manifest.diagnostics.clone();
+            diagnostics.workspace_root = options.out.clone();
+            let report = PlanReport {
+                out: &options.out,
+                changes: &changes,
+                diagnostics: &diagnostics,
+                warnings: &manifest.warnings,
+            };
+            let json = serde_json::to_string_pretty(&report).context("serialize dry-run plan")?;
+            println!("{json}");
+        }
+    }
+
+    Ok(())
+}
+
+fn print_plan(out: &Path, changes: &[FileChange], warnings: &[Warning], warnings_count: usize) {
+    println!("Dry run for {} (nothing was written)", out.display());
+    if changes.is_empty() {
+        println!("No changes.");
+    } else {
+        println!("Planned changes ({}):", changes.len());
+        for change in changes {
+            let verb = match change.kind {
+                ChangeKind::Create => "create",
+                ChangeKind::Modify => "modify",
+                ChangeKind::Delete => "delete",
+            };
+            println!("  {verb} {}", change.path);
+        }
+    }
+    println!("Warnings: {warnings_count}");
+    for warning in warnings.iter().filter(|w| !w.is_allowed()) {
+        println!("  - {warning}");
+    }
+    for change in changes {
+        if let Some(diff) = change.diff.as_deref() {
+            print!("\n{diff}");
+        }
+    }
+}
+
+/// Compares two workspace trees, skipping `dist/` and everything under `.cards2pack/` except the
+/// manifest (build state and scratch files change on every run).
+pub fn diff_trees(before: &Path, after: &Path) -> Result<Vec<FileChange>> {
+    let before_files = workspace_files(before);
+    let after_files = workspace_files(after);
+    let all: BTreeSet<&String> = before_files.iter().chain(after_files.iter()).collect();
+
+    let mut changes = Vec::new();
+    for rel in all {
+        let old = if before_files.contains(rel) {
+            Some(read_bytes(&before.join(rel))?)
+        } else {
+            None
+        };
+        let new = if after_files.contains(rel) {
+            Some(read_bytes(&after.join(rel))?)
+        } else {
+            None
+        };
+        let kind = match (&old, &new) {
+            (None, Some(_)) => ChangeKind::Create,
+            (Some(_), None) => ChangeKind::Delete,
+            (Some(old), Some(new)) if !same_contents(rel, [before, after], old, new) => {
+                ChangeKind::Modify
+            }
+            _ => continue,
+        };
+        let diff = if wants_diff(rel) {
+            let old_text = old
+                .as_deref()
+                .map(String::from_utf8_lossy)
+                .unwrap_or_default();
+            let new_text = new
+                .as_deref()
+                .map(String::from_utf8_lossy)
+                .unwrap_or_default();
+            let old_label = if old.is_some() {
+                format!("a/{rel}")
+            } else {
+                "/dev/null".to_string()
+            };
+            let new_label = if new.is_some() {
+                format!("b/{rel}")
+            } else {
+                "/dev/null".to_string()
+            };
+            Some(unified_diff(&old_text, &new_text, &old_label, &new_label))
+        } else {
+            None
+        };
+        changes.push(FileChange {
+            path: rel.clone(),
+            kind,
+            diff,
+        });
+    }
+
+    Ok(changes)
+}
+
+enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = diff_lines(&old_lines, &new_lines);
+
+    let changed: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, DiffLine::Same(_)))
+        .map(|(index, _)| index)
+        .collect();
+    if changed.is_empty() {
+        return String::new();
+    }
+
+    let mut old_pos = Vec::with_capacity(ops.len() + 1);
+    let mut new_pos = Vec::with_capacity(ops.len() + 1);
+    let (mut old_line, mut new_line) = (0, 0);
+    for op in &ops {
+        old_pos.push(old_line);
+        new_pos.push(new_line);
+        match op {
+            DiffLine::Same(_) => {
+                old_line += 1;
+                new_line += 1;
+            }
+            DiffLine::Removed(_) => old_line += 1,
+            DiffLine::Added(_) => new_line += 1,
+        }
+    }
+
+    let mut hunks = Vec::new();
+    let mut start = changed[0].saturating_sub(DIFF_CONTEXT);
+    let mut end = (changed[0] + DIFF_CONTEXT + 1).min(ops.len());
+    for &index in &changed[1..] {
+        if index.saturating_sub(DIFF_CONTEXT) <= end {
+            end = (index + DIFF_CONTEXT + 1).min(ops.len());
+        } else {
+            hunks.push((start, end));
+            start = index.saturating_sub(DIFF_CONTEXT);
+            end = (index + DIFF_CONTEXT + 1).min(ops.len());
+        }
+    }
+    hunks.push((start, end));
+
+    let mut output = format!("--- {old_label}\n+++ {new_label}\n");
+    for (start, end) in hunks {
+        let slice = &ops[start..end];
+        let old_len = slice
+            .iter()
+            .filter(|op| !matches!(op, DiffLine::Added(_)))
+            .count();
+        let new_len = slice
+            .iter()
+            .filter(|op| !matches!(op, DiffLine::Removed(_)))
+            .count();
+        output.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(old_pos[start], old_len),
+            hunk_range(new_pos[start], new_len)
+        ));
+        for op in slice {
+            match op {
+                DiffLine::Same(line) => output.push_str(&format!(" {line}\n")),
+                DiffLine::Removed(line) => output.push_str(&format!("-{line}\n")),
+                DiffLine::Added(line) => output.push_str(&format!("+{line}\n")),
+            }
+        }
+    }
+
+    output
+}
+
+fn hunk_range(start: usize, len: usize) -> String {
+    if len == 0 {
+        format!("{start},0")
+    } else {
+        format!("{},{len}", start + 1)
+    }
+}
+
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<DiffLine<'a>> {
+    let prefix = old
+        .iter()
+        .zip(new.iter())
+        .take_while(|(left, right)| left == right)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(left, right)| left == right)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let columns = new_mid.len() + 1;
+    let mut lcs = vec![0u32; (old_mid.len() + 1) * columns];
+    for i in (0..old_mid.len()).rev() {
+        for j in (0..new_mid.len()).rev() {
+            lcs[i * columns + j] = if old_mid[i] == new_mid[j] {
+                lcs[(i + 1) * columns + j + 1] + 1
+            } else {
+                lcs[(i + 1) * columns + j].max(lcs[i * columns + j + 1])
+            };
+        }
+    }
+
+    let mut ops: Vec<DiffLine<'a>> = old[..prefix]
+        .iter()
+        .map(|line| DiffLine::Same(line))
+        .collect();
+    let (mut i, mut j) = (0, 0);
+    while i < old_mid.len() && j < new_mid.len() {
+        if old_mid[i] == new_mid[j] {
+            ops.push(DiffLine::Same(old_mid[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[(i + 1) * columns + j] >= lcs[i * columns + j + 1] {
+            ops.push(DiffLine::Removed(old_mid[i]));
+            i += 1;
+        } else {
+            ops.push(DiffLine::Added(new_mid[j]));
+            j += 1;
+        }
+    }
+    ops.extend(old_mid[i..].iter().map(|line| DiffLine::Removed(line)));
+    ops.extend(new_mid[j..].iter().map(|line| DiffLine::Added(line)));
+    ops.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|line| DiffLine::Same(line)),
+    );
+    ops
+}
+
+/// Whether two versions of a workspace file are the same. The manifest records when and where it
+/// was generated, so its `generated_at`, the workspace root (the planned tree lives elsewhere) and
+/// `dist_artifact` (a dry run builds no pack) are left out of the comparison.
+fn same_contents(rel: &str, roots: [&Path; 2], old: &[u8], new: &[u8]) -> bool {
+    if old == new {
+        return true;
+    }
+    if rel != MANIFEST_PATH {
+        return false;
+    }
+    let roots = roots.map(|root| root.display().to_string());
+    let normalised = |bytes: &[u8]| {
+        let mut manifest: Value = serde_json::from_slice(bytes).ok()?;
+        let fields = manifest.as_object_mut()?;
+        fields.remove("generated_at");
+        fields
+            .get_mut("diagnostics")
+            .and_then(Value::as_object_mut)?
+            .remove("dist_artifact");
+        replace_roots(&mut manifest, &roots);
+        Some(manifest)
+    };
+    match (normalised(old), normalised(new)) {
+        (Some(old), Some(new)) => old == new,
+        _ => false,
+    }
+}
+
+/// Replaces `roots` with `<out>` in every string where one appears as a whole path, not as part
+/// of a longer name.
+fn replace_roots(value: &mut Value, roots: &[String]) {
+    match value {
+        Value::String(text) => {
+            for root in roots.iter().filter(|root| !root.is_empty()) {
+                *text = replace_path_prefix(text, root);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| replace_roots(item, roots)),
+        Value::Object(map) => map
+            .values_mut()
+            .for_each(|child| replace_roots(child, roots)),
+        _ => {}
+    }
+}
+
+fn replace_path_prefix(text: &str, root: &str) -> String {
+    let is_path_char = |c: char| c.is_alphanumeric() || "_-.~".contains(c);
+    let mut output = String::new();
+    let mut copied = 0;
+    for (index, _) in text.match_indices(root) {
+        let end = index + root.len();
+        let before = text[..index].chars().next_back();
+        let after = text[end..].chars().next();
+        if index < copied
+            || before.is_some_and(|c| is_path_char(c) || c == '/' || c == '\\')
+            || after.is_some_and(is_path_char)
+        {
+            continue;
+        }
+        output.push_str(&text[copied..index]);
+        output.push_str("<out>");
+        copied = end;
+    }
+    output.push_str(&text[copied..]);
+    output
+}
+
+fn wants_diff(rel: &str) -> bool {
+    rel == "README.md"
+        || rel == "pack.yaml"
+        || (rel.starts_with("flows/") && rel.ends_with(".ygtc"))
+}
+
+fn workspace_files(root: &Path) -> BTreeSet<String> {
+    let mut files = BTreeSet::new();
+    if !root.is_dir() {
+        return files;
+    }
+    for entry in WalkDir::new(root).into_iter().filter_map(Result::ok) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(rel) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        let rel = rel.to_string_lossy().replace('\\', "/");
+        if rel.starts_with("dist/") || (rel.starts_with(".cards2pack/") && rel != MANIFEST_PATH) {
+            continue;
+        }
+        files.insert(rel);
+    }
+    files
+}
+
+fn read_bytes(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path).with_context(|| format!("failed to read {}", path.display()))
+}
+
+fn copy_tree(source: &Path, dest: &Path) -> Result<()> {
+    for entry in WalkDir::new(source).into_iter().filter_map(Result::ok) {
+        let rel = entry
+            .path()
+            .strip_prefix(source)
+            .with_context(|| format!("failed to strip prefix for {}", entry.path().display()))?;
+        if rel.starts_with("dist") {
+            continue;
+        }
+        let target: PathBuf = dest.join(rel);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)
+                .with_context(|| format!("failed to create {}", target.display()))?;
+        } else if entry.file_type().is_file() {
+            fs::copy(entry.path(), &target)
+                .with_context(|| format!("failed to copy {}", entry.path().display()))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{replace_path_prefix, unified_diff};
+
+    #[test]
+    fn unified_diff_reports_changed_lines_with_context() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\n";
+        let new = "a\nb\nc\nD\ne\nf\ng\nh\ni\n";
+        let diff = unified_diff(old, new, "a/x", "b/x");
+        assert_eq!(
+            diff,
+            "--- a/x\n+++ b/x\n@@ -1,8 +1,9 @@\n a\n b\n c\n-d\n+D\n e\n f\n g\n h\n+i\n"
+        );
+    }
+
+    #[test]
+    fn unified_diff_is_empty_for_identical_input() {
+        assert!(unified_diff("same\n", "same\n", "a/x", "b/x").is_empty());
+    }
+
+    #[test]
+    fn replaces_workspace_root_only_as_a_whole_path() {
+        assert_eq!(
+            replace_path_prefix("out/flows/a.ygtc and layout/out.json: out", "out"),
+            "<out>/flows/a.ygtc and layout/out.json: <out>"
+        );
+    }
+}
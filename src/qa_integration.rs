@@ -4,12 +4,13 @@ use std::io::{self, Write};
 use std::path::Path;
 
 use anyhow::{Context, Result, bail};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 const PROMPT_SPEC_REL: &str = "qa/prompt2flow.form.json";
 
-#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, JsonSchema)]
 pub struct PromptLimits {
     pub max_intents: usize,
     pub max_examples_per_intent: usize,
@@ -141,7 +142,7 @@ pub fn persist_prompt2flow_config(config: &Prompt2FlowConfig, target: &Path) ->
     }
     let serialized = serde_json::to_string_pretty(config).context("serialize prompt2flow")?;
     fs::write(target, serialized).with_context(|| format!("write {}", target.display()))?;
-    println!("Saved prompt2flow config to {}", target.display());
+    eprintln!("Saved prompt2flow config to {}", target.display());
     Ok(())
 }
 
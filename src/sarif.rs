@@ -0,0 +1,237 @@
+use std::env;
+use std::path::{Component, Path};
+
+use serde_json::{Value, json};
+
+use crate::ir::{Location, Severity, Warning, WarningKind};
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// A SARIF 2.1.0 log with one run: a rule per warning kind and a result per warning.
+pub fn sarif_log(warnings: &[Warning]) -> Value {
+    let rules: Vec<Value> = WarningKind::ALL.iter().map(|kind| rule(*kind)).collect();
+    let results: Vec<Value> = warnings.iter().map(result).collect();
+    json!({
+        "$schema": SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }]
+    })
+}
+
+fn rule(kind: WarningKind) -> Value {
+    let (summary, help) = describe(kind);
+    json!({
+        "id": kind.code(),
+        "name": kind.name(),
+        "shortDescription": { "text": summary },
+        "fullDescription": { "text": format!("{summary} {help}") },
+        "help": { "text": help },
+        "defaultConfiguration": { "level": level(kind.default_severity()) },
+    })
+}
+
+fn result(warning: &Warning) -> Value {
+    let index = WarningKind::ALL
+        .iter()
+        .position(|kind| *kind == warning.kind)
+        .unwrap_or_default();
+    let mut value = json!({
+        "ruleId": warning.kind.code(),
+        "ruleIndex": index,
+        "level": level(warning.severity),
+        "message": { "text": warning.message },
+    });
+    if let Some(location) = &warning.location {
+        value["locations"] = json!([location_json(location)]);
+    }
+    let properties: serde_json::Map<String, Value> = [
+        ("cardId", &warning.card_id),
+        ("flow", &warning.flow),
+        ("relPath", &warning.rel_path),
+        ("target", &warning.target),
+        ("routeKey", &warning.route_key),
+        ("tool", &warning.tool),
+    ]
+    .into_iter()
+    .filter_map(|(key, field)| Some((key.to_string(), json!(field.as_ref()?))))
+    .collect();
+    if !properties.is_empty() {
+        value["properties"] = Value::Object(properties);
+    }
+    value
+}
+
+fn location_json(location: &Location) -> Value {
+    let mut physical = json!({ "artifactLocation": { "uri": artifact_uri(&location.file) } });
+    if let Some(line) = location.line {
+        let mut region = json!({ "startLine": line });
+        if let Some(column) = location.column {
+            region["startColumn"] = json!(column);
+        }
+        physical["region"] = region;
+    }
+    let mut value = json!({ "physicalLocation": physical });
+    if let Some(pointer) = &location.pointer {
+        value["logicalLocations"] = json!([{ "fullyQualifiedName": pointer, "kind": "element" }]);
+    }
+    value
+}
+
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Allow => "note",
+        Severity::Warn => "warning",
+        Severity::Deny => "error",
+    }
+}
+
+/// A path relative to the working directory (where CI checks the repository out) when the file
+/// lies under it, else a `file://` URI.
+fn artifact_uri(file: &str) -> String {
+    let path = Path::new(file);
+    let relative = match env::current_dir() {
+        Ok(cwd) if path.is_absolute() => path.strip_prefix(&cwd).ok(),
+        _ => Some(path),
+    };
+    match relative {
+        Some(relative) => {
+            let parts: Vec<String> = relative
+                .components()
+                .filter(|component| !matches!(component, Component::CurDir))
+                .map(|component| encode(&component.as_os_str().to_string_lossy()))
+                .collect();
+            parts.join("/")
+        }
+        None => {
+            let encoded = encode(&file.replace('\\', "/"));
+            if encoded.starts_with('/') {
+                format!("file://{encoded}")
+            } else {
+                format!("file:///{encoded}")
+            }
+        }
+    }
+}
+
+/// Percent-encodes everything but unreserved characters, `/` and a drive letter's `:`.
+fn encode(value: &str) -> String {
+    let mut encoded = String::new();
+    for (index, byte) in value.bytes().enumerate() {
+        if byte.is_ascii_alphanumeric()
+            || matches!(byte, b'-' | b'.' | b'_' | b'~' | b'/')
+            || (byte == b':' && index == 1)
+        {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+/// What a warning kind means and how to resolve it.
+fn describe(kind: WarningKind) -> (&'static str, &'static str) {
+    match kind {
+        WarningKind::Inconsistent => (
+            "Card metadata disagrees with itself or with the grouping strategy.",
+            "Make the card's actions, greentic fields, locale and file name agree; routes that \
+             close a cycle are also reported here.",
+        ),
+        WarningKind::MissingTarget => (
+            "An action routes to a card that does not exist in the flow.",
+            "Add the target card or fix the action's step or cardId; a stub node is emitted \
+             until then.",
+        ),
+        WarningKind::MissingFlow => (
+            "A card has no flow name.",
+            "Set greentic.flow, the action flow field or the grouping folder, or pass \
+             --default-flow; the card is placed in a fallback flow until then.",
+        ),
+        WarningKind::MissingCardId => (
+            "A card has no card id.",
+            "Set greentic.cardId or the action cardId; the file name is used until then.",
+        ),
+        WarningKind::DuplicateCardId => (
+            "Two cards in one flow share a card id and locale.",
+            "Give each card a unique cardId; only the first card is used.",
+        ),
+        WarningKind::InvalidJson => (
+            "A card or template data file could not be read or parsed.",
+            "Fix the syntax error at the reported position; the file is skipped until then.",
+        ),
+        WarningKind::IgnoredFile => (
+            "A file under the cards directory is not an Adaptive Card.",
+            "Safe to ignore for supporting data; otherwise add type \"AdaptiveCard\" or exclude \
+             the file with --exclude.",
+        ),
+        WarningKind::PackOutput => (
+            "The pack workspace or artifact is not where it was expected.",
+            "Check the greentic-pack output and the component environment variables.",
+        ),
+        WarningKind::Validation => (
+            "greentic-flow or greentic-pack validation failed.",
+            "Run the reported doctor or resolve command for details.",
+        ),
+        WarningKind::UnboundVariable => (
+            "A templated card uses variables its data file does not bind.",
+            "Add the variables to the card's .data.json file or remove them from the card.",
+        ),
+        WarningKind::Compatibility => (
+            "A card uses a feature its declared version or the --host profile does not support.",
+            "Raise the card's version, or drop the element or property.",
+        ),
+        WarningKind::MissingLocale => (
+            "A localized card has no variant for a locale other cards in the flow provide.",
+            "Add the translation; the unlocalized asset is used for that locale until then.",
+        ),
+        WarningKind::NoRoutes => (
+            "A card has no outgoing route and ends its flow.",
+            "Expected for final cards; otherwise add a submit action with a target.",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sarif_log;
+    use crate::diagnostics::warning;
+    use crate::ir::{Location, WarningKind};
+
+    #[test]
+    fn writes_rules_and_located_results() {
+        let warnings = vec![
+            warning(WarningKind::MissingTarget, "missing target b")
+                .target("b")
+                .at(Location::position("cards/my card.json", 7, 5)),
+            warning(WarningKind::Validation, "greentic-flow doctor failed"),
+        ];
+        let log = sarif_log(&warnings);
+        let run = &log["runs"][0];
+
+        let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+        assert_eq!(rules.len(), WarningKind::ALL.len());
+        assert_eq!(rules[1]["id"], "C2P002");
+        assert_eq!(rules[1]["name"], "missing_target");
+
+        let result = &run["results"][0];
+        assert_eq!(result["ruleId"], "C2P002");
+        assert_eq!(result["ruleIndex"], 1);
+        assert_eq!(result["level"], "warning");
+        let physical = &result["locations"][0]["physicalLocation"];
+        assert_eq!(physical["artifactLocation"]["uri"], "cards/my%20card.json");
+        assert_eq!(physical["region"]["startLine"], 7);
+        assert_eq!(physical["region"]["startColumn"], 5);
+        assert_eq!(result["properties"]["target"], "b");
+        assert!(run["results"][1].get("locations").is_none());
+    }
+}
@@ -3,225 +3,301 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, anyhow, bail};
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 use time::OffsetDateTime;
 use time::format_description::well_known::Rfc3339;
-use walkdir::WalkDir;
 
-use crate::cli::GroupBy;
-use crate::diagnostics::warning;
+use crate::card_files::{CardFilter, list_card_files, rel_string};
+use crate::card_source::{CardFormat, asset_rel_path};
+use crate::cli::{GroupBy, HostProfile};
+use crate::compat::compatibility_issues;
+use crate::diagnostics::{SeverityPolicy, StrictErrors, warning};
+use crate::inputs::input_schema;
 use crate::ir::{
-    CardAction, CardDoc, FlowGroup, FlowSummary, InputInfo, Manifest, RouteTarget, Warning,
-    WarningKind,
+    CardAction, CardDoc, FlowGroup, FlowSummary, InputInfo, Location, Manifest, RouteTarget,
+    Severity, Warning, WarningKind,
 };
+use crate::locale::{LOCALE_FIELD, declared_locale, split_locale_suffix};
+use crate::manifest::MANIFEST_VERSION;
+use crate::parallel::{map_ordered, worker_count};
+use crate::source_map::{SourceMap, error_position, escape_pointer_token};
+use crate::template::{
+    card_stem_for_data, data_file_for, expand_template, is_data_file, unbound_variables,
+};
+
+/// JSON pointer read by `--group-by flow-field` when no `--flow-field` is given.
+pub const DEFAULT_FLOW_FIELD: &str = "/greentic/flow";
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ScanConfig {
     pub cards_dir: PathBuf,
     pub group_by: Option<GroupBy>,
+    /// JSON pointer holding the flow name for `GroupBy::FlowField`.
+    pub flow_field: Option<String>,
     pub default_flow: Option<String>,
     pub strict: bool,
+    pub routing_keys: RoutingKeys,
+    /// `--include` globs relative to `cards_dir`; when set, only matching files are scanned.
+    pub include: Vec<String>,
+    /// `--exclude` globs relative to `cards_dir`.
+    pub exclude: Vec<String>,
+    /// Worker threads for parsing cards; `None` or 0 uses the available parallelism.
+    pub jobs: Option<usize>,
+    /// Host whose schema version and element support cards are checked against.
+    pub host: Option<HostProfile>,
+    /// Strict mode stops collecting errors after this many; `None` or 0 reports all of them.
+    pub max_errors: Option<usize>,
+    /// `--allow`/`--warn`/`--deny` overrides by warning kind.
+    pub severities: BTreeMap<WarningKind, Severity>,
+    /// Cards with sample data are routed as packaged by `--expand-templates`: with their
+    /// bindings expanded.
+    pub expand_templates: bool,
+    /// `--locale-suffixes`: `<card>.<locale>.json` is the `<locale>` variant of card `<card>`.
+    pub locale_suffixes: bool,
 }
 
-pub fn scan_cards(config: &ScanConfig) -> Result<Manifest> {
-    let mut warnings: Vec<Warning> = Vec::new();
-    let mut cards = Vec::new();
-
-    for entry in WalkDir::new(&config.cards_dir)
-        .into_iter()
-        .filter_map(Result::ok)
-    {
-        if !entry.file_type().is_file() {
-            continue;
-        }
+/// JSON pointers that locate routing metadata; in each list the first pointer holding a string wins.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct RoutingKeys {
+    /// Card-level pointers for the card id.
+    #[serde(deserialize_with = "one_or_many")]
+    pub card_id: Vec<String>,
+    /// Card-level pointers for the flow name.
+    #[serde(deserialize_with = "one_or_many")]
+    pub flow: Vec<String>,
+    /// Action pointers naming the card id this action belongs to or routes to.
+    #[serde(deserialize_with = "one_or_many")]
+    pub action_card_id: Vec<String>,
+    /// Action pointers for the flow name.
+    #[serde(deserialize_with = "one_or_many")]
+    pub action_flow: Vec<String>,
+    /// Action pointers for the route target step; checked before `action_card_id`.
+    #[serde(deserialize_with = "one_or_many")]
+    pub target: Vec<String>,
+}
 
-        let path = entry.path();
-        let extension = path.extension().and_then(|ext| ext.to_str());
-        if extension.is_none_or(|ext| !ext.eq_ignore_ascii_case("json")) {
-            continue;
+impl Default for RoutingKeys {
+    fn default() -> Self {
+        Self {
+            card_id: vec!["/greentic/cardId".to_string()],
+            flow: vec![DEFAULT_FLOW_FIELD.to_string()],
+            action_card_id: vec!["/data/cardId".to_string()],
+            action_flow: vec!["/data/flow".to_string()],
+            target: vec!["/data/step".to_string()],
         }
+    }
+}
 
-        let contents = match fs::read_to_string(path) {
-            Ok(contents) => contents,
-            Err(err) => {
-                warnings.push(warning(
-                    WarningKind::InvalidJson,
-                    format!("failed to read {}: {err}", path.display()),
-                ));
-                continue;
-            }
-        };
+impl RoutingKeys {
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
 
-        let value: Value = match serde_json::from_str(&contents) {
-            Ok(value) => value,
-            Err(err) => {
-                if config.strict {
-                    bail!("invalid JSON in {}: {err}", path.display());
+    fn validate(&self) -> Result<()> {
+        for (name, pointers) in [
+            ("card_id", &self.card_id),
+            ("flow", &self.flow),
+            ("action_card_id", &self.action_card_id),
+            ("action_flow", &self.action_flow),
+            ("target", &self.target),
+        ] {
+            for pointer in pointers {
+                if !pointer.starts_with('/') {
+                    bail!("routing key {name} must be a JSON pointer starting with '/': {pointer}");
                 }
-                warnings.push(warning(
-                    WarningKind::InvalidJson,
-                    format!("invalid JSON in {}: {err}", path.display()),
-                ));
-                continue;
-            }
-        };
-
-        let object = match value.as_object() {
-            Some(object) => object,
-            None => {
-                warnings.push(warning(
-                    WarningKind::IgnoredFile,
-                    format!("non-object JSON ignored: {}", path.display()),
-                ));
-                continue;
             }
-        };
-
-        if let Some(card_type) = object.get("type").and_then(|value| value.as_str()) {
-            if card_type != "AdaptiveCard" {
-                warnings.push(warning(
-                    WarningKind::IgnoredFile,
-                    format!(
-                        "non-AdaptiveCard JSON ignored: {} (type={})",
-                        path.display(),
-                        card_type
-                    ),
-                ));
-                continue;
-            }
-        } else if !object.contains_key("actions") && !object.contains_key("body") {
-            warnings.push(warning(
-                WarningKind::IgnoredFile,
-                format!("non-card JSON ignored: {}", path.display()),
-            ));
-            continue;
         }
+        Ok(())
+    }
+}
 
-        let actions_value = object.get("actions").and_then(|value| value.as_array());
-        let actions_value = actions_value.map(|array| array.as_slice()).unwrap_or(&[]);
-
-        let mut action_card_ids = Vec::new();
-        let mut action_flow_names = Vec::new();
-        let mut actions = Vec::new();
-
-        for action in actions_value {
-            let action_obj = match action.as_object() {
-                Some(action_obj) => action_obj,
-                None => {
-                    warnings.push(warning(
-                        WarningKind::IgnoredFile,
-                        format!("ignored non-object action in {}", path.display()),
-                    ));
-                    continue;
-                }
-            };
-
-            let action_type = action_obj
-                .get("type")
-                .and_then(|value| value.as_str())
-                .unwrap_or("Unknown")
-                .to_string();
-            let title = action_obj
-                .get("title")
-                .and_then(|value| value.as_str())
-                .map(|value| value.to_string());
-
-            let data = action_obj.get("data").cloned().unwrap_or(Value::Null);
-            let data_obj = action_obj.get("data").and_then(|value| value.as_object());
-
-            if let Some(card_id) = data_obj
-                .and_then(|obj| obj.get("cardId"))
-                .and_then(|value| value.as_str())
-            {
-                action_card_ids.push(card_id.to_string());
-            }
+fn one_or_many<'de, D>(deserializer: D) -> std::result::Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
 
-            if let Some(flow) = data_obj
-                .and_then(|obj| obj.get("flow"))
-                .and_then(|value| value.as_str())
-            {
-                action_flow_names.push(flow.to_string());
-            }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => vec![value],
+        OneOrMany::Many(values) => values,
+    })
+}
 
-            let target = if let Some(step) = data_obj
-                .and_then(|obj| obj.get("step"))
-                .and_then(|value| value.as_str())
-            {
-                Some(RouteTarget::Step(step.to_string()))
-            } else {
-                data_obj
-                    .and_then(|obj| obj.get("cardId"))
-                    .and_then(|value| value.as_str())
-                    .map(|card_id| RouteTarget::CardId(card_id.to_string()))
-            };
+impl ScanConfig {
+    pub fn flow_field(&self) -> &str {
+        self.flow_field.as_deref().unwrap_or(DEFAULT_FLOW_FIELD)
+    }
 
-            actions.push(CardAction {
-                action_type,
-                title,
-                target,
-                data,
-            });
+    pub fn policy(&self) -> SeverityPolicy<'_> {
+        SeverityPolicy {
+            strict: self.strict,
+            overrides: &self.severities,
         }
+    }
+}
+
+/// Scans the cards directory. In strict mode every violation is collected and reported together.
+pub fn scan_cards(config: &ScanConfig) -> Result<Manifest> {
+    let mut errors = StrictErrors::new(config.max_errors);
+    let manifest = scan_cards_collecting(config, &mut errors)?;
+    errors.finish_with(&manifest.warnings)?;
+    Ok(manifest)
+}
 
-        let rel_path = path
-            .strip_prefix(&config.cards_dir)
-            .with_context(|| format!("failed to strip prefix for {}", path.display()))?;
+/// Like [`scan_cards`], but leaves strict-mode violations in `errors` so later phases can add
+/// theirs before the run fails.
+pub fn scan_cards_collecting(config: &ScanConfig, errors: &mut StrictErrors) -> Result<Manifest> {
+    let filter = CardFilter::load(&config.cards_dir, &config.include, &config.exclude)?;
+    let files = list_card_files(&config.cards_dir, &filter)?;
+    scan_card_files(config, &files, errors)
+}
 
-        let rel_path_string = rel_path.to_string_lossy().replace('\\', "/").to_string();
+/// Scans an already filtered file list from [`list_card_files`], collecting strict-mode
+/// violations in `errors`.
+pub fn scan_card_files(
+    config: &ScanConfig,
+    files: &[PathBuf],
+    errors: &mut StrictErrors,
+) -> Result<Manifest> {
+    let flow_field = config.flow_field();
+    if !flow_field.starts_with('/') {
+        bail!("flow field must be a JSON pointer starting with '/': {flow_field}");
+    }
+    config.routing_keys.validate()?;
+    if let Some(default_flow) = config.default_flow.as_deref()
+        && !is_valid_flow_name(default_flow)
+    {
+        bail!(
+            "default flow must be a single segment of letters, digits, '_' or '-': {default_flow}"
+        );
+    }
+    let keys = &config.routing_keys;
 
-        let card_id = resolve_card_id(
-            &action_card_ids,
-            object,
-            &rel_path_string,
-            config,
-            &mut warnings,
-        )?;
+    // Cards are parsed in parallel; results (and their warnings) are merged in file order.
+    let sources: Vec<&Path> = files
+        .iter()
+        .map(PathBuf::as_path)
+        .filter(|path| CardFormat::from_path(path).is_some() && !is_data_file(path))
+        .collect();
+    let scanned = map_ordered(&sources, worker_count(config.jobs), |path| -> Result<_> {
+        let mut warnings = Vec::new();
+        let mut errors = StrictErrors::new(None);
+        let card = scan_card_file(path, files, config, &mut warnings, &mut errors)?;
+        // Findings raised before the card id and flow were resolved get them now.
+        if let Some(card) = &card {
+            for finding in warnings.iter_mut().chain(errors.iter_mut()) {
+                finding.card_id.get_or_insert_with(|| card.card_id.clone());
+                finding.flow.get_or_insert_with(|| card.flow_name.clone());
+            }
+        }
+        Ok((card, warnings, errors))
+    });
 
-        let flow_name =
-            resolve_flow_name(&action_flow_names, object, rel_path, config, &mut warnings)?;
+    let mut warnings: Vec<Warning> = Vec::new();
+    let mut cards = Vec::new();
+    for result in scanned {
+        let (card, card_warnings, card_errors) = result?;
+        warnings.extend(card_warnings);
+        errors.extend(card_errors);
+        cards.extend(card);
+    }
 
-        cards.push(CardDoc {
-            rel_path: rel_path_string,
-            abs_path: path.to_path_buf(),
-            card_id,
-            flow_name,
-            actions,
+    for data_file in files.iter().filter(|path| is_data_file(path)) {
+        let has_card = card_stem_for_data(data_file).is_some_and(|stem| {
+            cards.iter().any(|card| {
+                card.abs_path.parent() == data_file.parent()
+                    && card.abs_path.file_stem().and_then(|value| value.to_str()) == Some(stem)
+            })
         });
+        if !has_card {
+            warnings.push(
+                warning(
+                    WarningKind::IgnoredFile,
+                    format!(
+                        "template data without a card ignored: {}",
+                        data_file.display()
+                    ),
+                )
+                .at(Location::file(data_file.display().to_string()))
+                .rel_path(rel_string(&config.cards_dir, data_file)),
+            );
+        }
     }
 
     if cards.is_empty() {
-        if config.strict {
-            bail!(
-                "no Adaptive Card JSON files found in {}",
-                config.cards_dir.display()
-            );
-        }
-        warnings.push(warning(
-            WarningKind::IgnoredFile,
-            "no Adaptive Card JSON files found".to_string(),
-        ));
+        let cards_dir = config.cards_dir.display().to_string();
+        config.policy().report(
+            warning(
+                WarningKind::IgnoredFile,
+                format!("no Adaptive Card JSON files found in {cards_dir}"),
+            )
+            .at(Location::file(cards_dir)),
+            &mut warnings,
+            errors,
+        );
     }
 
     let mut flows: BTreeMap<String, Vec<CardDoc>> = BTreeMap::new();
-    let mut seen: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+    // Localized variants share a card id; only the same id *and* locale is a duplicate.
+    type VariantKey = (String, Option<String>);
+    let mut seen: BTreeMap<String, BTreeMap<VariantKey, String>> = BTreeMap::new();
     for card in cards {
         let flow_name = card.flow_name.clone();
         let flow_seen = seen.entry(flow_name.clone()).or_default();
-        if let Some(existing) = flow_seen.get(&card.card_id) {
+        let key = (card.card_id.clone(), card.locale.clone());
+        if let Some(existing) = flow_seen.get(&key) {
             let message = format!(
                 "duplicate card_id {} in flow {}: {} and {}",
                 card.card_id, flow_name, existing, card.rel_path
             );
-            if config.strict {
-                bail!(message);
-            }
-            warnings.push(warning(WarningKind::DuplicateCardId, message));
+            config.policy().report(
+                warning(WarningKind::DuplicateCardId, message)
+                    .at(card.source.root())
+                    .for_card(&card),
+                &mut warnings,
+                errors,
+            );
             continue;
         }
-        flow_seen.insert(card.card_id.clone(), card.rel_path.clone());
+        flow_seen.insert(key, card.rel_path.clone());
         flows.entry(flow_name).or_default().push(card);
     }
 
+    for cards in flows.values() {
+        for card in cards.iter().filter(|card| card.id_from_locale_suffix) {
+            let Some(other) = cards
+                .iter()
+                .find(|other| other.card_id == card.card_id && !is_sibling_variant(card, other))
+            else {
+                continue;
+            };
+            let message = format!(
+                "{} is locale {} of card {} by its file name, but {} also defines card {}; give one \
+                 of them its own cardId",
+                card.rel_path,
+                card.locale.as_deref().unwrap_or_default(),
+                card.card_id,
+                other.rel_path,
+                card.card_id
+            );
+            config.policy().report(
+                warning(WarningKind::Inconsistent, message)
+                    .at(card.source.root())
+                    .for_card(card),
+                &mut warnings,
+                errors,
+            );
+        }
+    }
+
     let mut summaries = Vec::new();
     let mut flow_groups = Vec::new();
     for (flow_name, mut cards) in flows {
@@ -234,14 +310,19 @@ pub fn scan_cards(config: &ScanConfig) -> Result<Manifest> {
     }
 
     let cards_total = cards_count(&flow_groups);
+    config.policy().apply(&mut warnings, errors);
 
     Ok(Manifest {
-        version: 1,
+        version: MANIFEST_VERSION,
         generated_at: now_rfc3339(),
         input: InputInfo {
             cards_dir: config.cards_dir.clone(),
             group_by: config.group_by,
+            flow_field: (config.group_by == Some(GroupBy::FlowField))
+                .then(|| flow_field.to_string()),
             default_flow: config.default_flow.clone(),
+            routing_keys: (!keys.is_default()).then(|| keys.clone()),
+            host: config.host,
         },
         flows: flow_groups,
         warnings: warnings.clone(),
@@ -251,100 +332,676 @@ pub fn scan_cards(config: &ScanConfig) -> Result<Manifest> {
             Vec::new(),
             summaries,
             cards_total,
-            warnings.len(),
+            &warnings,
         ),
+        config: None,
     })
 }
 
+/// Whether `other` belongs with the variant `card` (whose card id is its file stem without the
+/// locale suffix): it is `card` itself, the unlocalized `<id>.json` next to it, or another
+/// `<id>.<locale>.json` variant there.
+fn is_sibling_variant(card: &CardDoc, other: &CardDoc) -> bool {
+    let path = Path::new(&card.rel_path);
+    let other_path = Path::new(&other.rel_path);
+    let other_stem = other_path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default();
+    path.parent() == other_path.parent()
+        && (other.id_from_locale_suffix || other_stem == card.card_id)
+}
+
+/// Parses one card source. Returns `None` for files that are not Adaptive Cards; the reason is
+/// pushed to `warnings` (or `errors` when its kind is denied).
+fn scan_card_file(
+    path: &Path,
+    files: &[PathBuf],
+    config: &ScanConfig,
+    warnings: &mut Vec<Warning>,
+    errors: &mut StrictErrors,
+) -> Result<Option<CardDoc>> {
+    let keys = &config.routing_keys;
+    let Some(format) = CardFormat::from_path(path) else {
+        return Ok(None);
+    };
+    let rel_path_string = rel_string(&config.cards_dir, path);
+
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) => {
+            warnings.push(
+                warning(
+                    WarningKind::InvalidJson,
+                    format!("failed to read {}: {err}", path.display()),
+                )
+                .at(Location::file(path.display().to_string()))
+                .rel_path(&rel_path_string),
+            );
+            return Ok(None);
+        }
+    };
+
+    let value: Value = match format.parse(&contents) {
+        Ok(value) => value,
+        Err(err) => {
+            let message = format!("invalid {} in {}: {err}", format.label(), path.display());
+            config.policy().report(
+                warning(WarningKind::InvalidJson, message)
+                    .at(parse_error_location(path, &err))
+                    .rel_path(rel_path_string),
+                warnings,
+                errors,
+            );
+            return Ok(None);
+        }
+    };
+    let source = SourceMap::new(path.display().to_string(), &contents, format);
+
+    let object = match value.as_object() {
+        Some(object) => object,
+        None => {
+            warnings.push(
+                warning(
+                    WarningKind::IgnoredFile,
+                    format!("non-object JSON ignored: {}", path.display()),
+                )
+                .at(source.root())
+                .rel_path(rel_path_string),
+            );
+            return Ok(None);
+        }
+    };
+
+    if let Some(card_type) = object.get("type").and_then(|value| value.as_str()) {
+        if card_type != "AdaptiveCard" {
+            warnings.push(
+                warning(
+                    WarningKind::IgnoredFile,
+                    format!(
+                        "non-AdaptiveCard JSON ignored: {} (type={})",
+                        path.display(),
+                        card_type
+                    ),
+                )
+                .at(source.locate("/type"))
+                .rel_path(rel_path_string),
+            );
+            return Ok(None);
+        }
+    } else if !object.contains_key("actions") && !object.contains_key("body") {
+        warnings.push(
+            warning(
+                WarningKind::IgnoredFile,
+                format!("non-card JSON ignored: {}", path.display()),
+            )
+            .at(source.root())
+            .rel_path(rel_path_string),
+        );
+        return Ok(None);
+    }
+
+    let template_data = load_template_data(path, files, &value, &source, config, warnings, errors)?;
+    // Routing, ids and inputs come from the card as packaged.
+    let expanded = match &template_data {
+        Some((_, Some(data))) if config.expand_templates => Some(expand_template(&value, data)),
+        _ => None,
+    };
+    let card = expanded.as_ref().unwrap_or(&value);
+
+    let mut discovered = Vec::new();
+    let mut ignored_actions = Vec::new();
+    discover_actions(
+        card,
+        String::new(),
+        false,
+        &mut discovered,
+        &mut ignored_actions,
+    );
+    for action_path in ignored_actions {
+        warnings.push(
+            warning(
+                WarningKind::IgnoredFile,
+                format!(
+                    "ignored non-object action at {action_path} in {}",
+                    path.display()
+                ),
+            )
+            .at(source.locate(&action_path))
+            .rel_path(&rel_path_string),
+        );
+    }
+
+    let mut action_card_ids = Vec::new();
+    let mut action_flow_names = Vec::new();
+    let mut actions = Vec::new();
+
+    for (action_path, action) in discovered {
+        let action_type = action
+            .get("type")
+            .and_then(|value| value.as_str())
+            .unwrap_or("Unknown")
+            .to_string();
+        let title = action
+            .get("title")
+            .and_then(|value| value.as_str())
+            .map(|value| value.to_string());
+
+        let data = action.get("data").cloned().unwrap_or(Value::Null);
+        let action_card_id = first_pointer_str(action, &keys.action_card_id);
+
+        if let Some(card_id) = action_card_id.as_ref() {
+            action_card_ids.push((card_id.clone(), action_path.clone()));
+        }
+
+        if let Some(flow) = first_pointer_str(action, &keys.action_flow) {
+            action_flow_names.push((flow, action_path.clone()));
+        }
+
+        let verb = action
+            .get("verb")
+            .and_then(|value| value.as_str())
+            .filter(|value| !value.is_empty())
+            .map(|value| value.to_string());
+
+        let target = first_pointer_str(action, &keys.target)
+            .map(RouteTarget::Step)
+            .or_else(|| action_card_id.map(RouteTarget::CardId))
+            .or_else(|| verb.clone().map(RouteTarget::Verb));
+
+        actions.push(CardAction {
+            path: action_path,
+            action_type,
+            title,
+            verb,
+            target,
+            data,
+        });
+    }
+
+    let rel_path = path
+        .strip_prefix(&config.cards_dir)
+        .with_context(|| format!("failed to strip prefix for {}", path.display()))?;
+
+    let name_locale = rel_path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .filter(|_| config.locale_suffixes)
+        .and_then(split_locale_suffix)
+        .map(|(_, locale)| locale);
+    let locale = match (declared_locale(card), name_locale) {
+        (Some(declared), Some(from_name)) if declared != from_name => {
+            let message = format!(
+                "{rel_path_string} declares locale {declared} but its file name says {from_name}"
+            );
+            config.policy().report(
+                warning(WarningKind::Inconsistent, message)
+                    .at(source.locate(LOCALE_FIELD))
+                    .rel_path(&rel_path_string),
+                warnings,
+                errors,
+            );
+            Some(declared)
+        }
+        (declared, from_name) => declared.or(from_name),
+    };
+
+    for issue in compatibility_issues(card, &rel_path_string, config.host) {
+        config.policy().report(
+            warning(WarningKind::Compatibility, issue.message)
+                .at(source.locate(&issue.pointer))
+                .rel_path(&rel_path_string),
+            warnings,
+            errors,
+        );
+    }
+
+    let (card_id, id_from_locale_suffix) = resolve_card_id(
+        &action_card_ids,
+        card,
+        &rel_path_string,
+        &source,
+        config,
+        warnings,
+        errors,
+    )?;
+
+    let flow_name = resolve_flow_name(
+        &action_flow_names,
+        card,
+        rel_path,
+        &source,
+        config,
+        warnings,
+        errors,
+    )?;
+    let flow_name = checked_flow_name(
+        flow_name,
+        &rel_path_string,
+        &source,
+        config,
+        warnings,
+        errors,
+    )?;
+
+    let asset_path = asset_rel_path(rel_path)
+        .to_string_lossy()
+        .replace('\\', "/");
+    let (data_path, data_asset_path) = match &template_data {
+        Some((data_abs, _)) => {
+            let data_rel = data_abs
+                .strip_prefix(&config.cards_dir)
+                .with_context(|| format!("failed to strip prefix for {}", data_abs.display()))?;
+            // An expanded card has no bindings left, so the runtime needs no data asset.
+            let data_asset_path = expanded.is_none().then(|| {
+                asset_rel_path(data_rel)
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            });
+            (
+                Some(data_rel.to_string_lossy().replace('\\', "/")),
+                data_asset_path,
+            )
+        }
+        None => (None, None),
+    };
+
+    let input_schema = input_schema(card, &card_id, &rel_path_string, &source, warnings);
+    let schema_asset_path = input_schema.as_ref().map(|_| {
+        rel_path
+            .with_extension("schema.json")
+            .to_string_lossy()
+            .replace('\\', "/")
+    });
+
+    Ok(Some(CardDoc {
+        rel_path: rel_path_string,
+        asset_path,
+        data_path,
+        data_asset_path,
+        input_schema,
+        schema_asset_path,
+        abs_path: path.to_path_buf(),
+        card_id,
+        locale,
+        id_from_locale_suffix,
+        flow_name,
+        actions,
+        source,
+    }))
+}
+
+/// Parses the card's `<stem>.data.*` sample data, if any, and reports template bindings it leaves
+/// unbound. Returns the data file path and, when it parses, the data.
+fn load_template_data(
+    card_path: &Path,
+    files: &[PathBuf],
+    card: &Value,
+    source: &SourceMap,
+    config: &ScanConfig,
+    warnings: &mut Vec<Warning>,
+    errors: &mut StrictErrors,
+) -> Result<Option<(PathBuf, Option<Value>)>> {
+    let Some(data_path) = data_file_for(card_path, files) else {
+        return Ok(None);
+    };
+    let format = CardFormat::from_path(&data_path).unwrap_or(CardFormat::Json);
+    let data = fs::read_to_string(&data_path)
+        .map_err(anyhow::Error::from)
+        .and_then(|contents| format.parse(&contents));
+    let data = match data {
+        Ok(data) => data,
+        Err(err) => {
+            let message = format!(
+                "invalid {} in {}: {err}",
+                format.label(),
+                data_path.display()
+            );
+            config.policy().report(
+                warning(WarningKind::InvalidJson, message)
+                    .at(parse_error_location(&data_path, &err))
+                    .rel_path(rel_string(&config.cards_dir, &data_path)),
+                warnings,
+                errors,
+            );
+            return Ok(Some((data_path, None)));
+        }
+    };
+
+    let unbound = unbound_variables(card, &data);
+    if !unbound.is_empty() {
+        let message = format!(
+            "template variables in {} not bound by {}: {}",
+            card_path.display(),
+            data_path.display(),
+            unbound.join(", ")
+        );
+        config.policy().report(
+            warning(WarningKind::UnboundVariable, message)
+                .at(source.root())
+                .rel_path(rel_string(&config.cards_dir, card_path)),
+            warnings,
+            errors,
+        );
+    }
+    Ok(Some((data_path, Some(data))))
+}
+
 fn resolve_card_id(
-    action_card_ids: &[String],
-    object: &serde_json::Map<String, Value>,
+    action_card_ids: &[(String, String)],
+    card: &Value,
     rel_path: &str,
+    source: &SourceMap,
     config: &ScanConfig,
     warnings: &mut Vec<Warning>,
-) -> Result<String> {
-    if let Some(value) =
-        resolve_consistent_value(action_card_ids, "cardId", rel_path, config.strict, warnings)?
-    {
-        return Ok(value);
+    errors: &mut StrictErrors,
+) -> Result<(String, bool)> {
+    if let Some(value) = resolve_consistent_value(
+        action_card_ids,
+        "cardId",
+        rel_path,
+        source,
+        config.policy(),
+        warnings,
+        errors,
+    )? {
+        return Ok((value, false));
     }
 
-    if let Some(value) = object
-        .get("greentic")
-        .and_then(|value| value.as_object())
-        .and_then(|obj| obj.get("cardId"))
-        .and_then(|value| value.as_str())
-    {
-        return Ok(value.to_string());
+    if let Some(value) = first_pointer_str(card, &config.routing_keys.card_id) {
+        return Ok((value, false));
     }
 
     let stem = Path::new(rel_path)
         .file_stem()
         .and_then(|value| value.to_str())
         .ok_or_else(|| anyhow!("unable to determine card id for {rel_path}"))?;
-
-    Ok(stem.to_string())
+    // With `--locale-suffixes`, `welcome.de.json` is the `de` variant of card `welcome`.
+    match split_locale_suffix(stem).filter(|_| config.locale_suffixes) {
+        Some((base, _)) => Ok((base.to_string(), true)),
+        None => Ok((stem.to_string(), false)),
+    }
 }
 
 fn resolve_flow_name(
-    action_flow_names: &[String],
-    object: &serde_json::Map<String, Value>,
+    action_flow_names: &[(String, String)],
+    card: &Value,
     rel_path: &Path,
+    source: &SourceMap,
     config: &ScanConfig,
     warnings: &mut Vec<Warning>,
+    errors: &mut StrictErrors,
 ) -> Result<String> {
-    if let Some(value) = resolve_consistent_value(
+    let rel_display = rel_path.display().to_string();
+    let action_flow = resolve_consistent_value(
         action_flow_names,
         "flow",
-        &rel_path.display().to_string(),
-        config.strict,
+        &rel_display,
+        source,
+        config.policy(),
         warnings,
-    )? {
-        return Ok(value);
+        errors,
+    )?;
+    let card_flow = first_pointer_str(card, &config.routing_keys.flow);
+
+    match config.group_by {
+        // Strict mode places every card by its folder, so flows never mix strategies.
+        Some(GroupBy::Folder) if config.strict => grouped_flow_name(
+            first_folder_component(rel_path),
+            "folder",
+            &[("action flow", action_flow), ("card flow", card_flow)],
+            &rel_display,
+            source,
+            config,
+            warnings,
+            errors,
+        ),
+        Some(GroupBy::FlowField) => {
+            let pointer = config.flow_field();
+            grouped_flow_name(
+                pointer_str(card, pointer),
+                &format!("flow field {pointer}"),
+                &[("action flow", action_flow), ("card flow", card_flow)],
+                &rel_display,
+                source,
+                config,
+                warnings,
+                errors,
+            )
+        }
+        group_by => {
+            // The folder only places cards that name no flow themselves.
+            let folder = (group_by == Some(GroupBy::Folder))
+                .then(|| first_folder_component(rel_path))
+                .flatten();
+            if let Some(value) = action_flow.or(card_flow).or(folder) {
+                return Ok(value);
+            }
+            if let Some(default_flow) = config.default_flow.as_ref() {
+                return Ok(default_flow.clone());
+            }
+            let message = if config.policy().denies(WarningKind::MissingFlow) {
+                format!("unable to resolve flow name for {rel_display}")
+            } else {
+                format!("flow name missing for {rel_display}; using misc")
+            };
+            config.policy().report(
+                warning(WarningKind::MissingFlow, message)
+                    .at(source.root())
+                    .rel_path(&rel_display)
+                    .flow("misc"),
+                warnings,
+                errors,
+            );
+            Ok("misc".to_string())
+        }
     }
+}
 
-    if let Some(value) = object
-        .get("greentic")
-        .and_then(|value| value.as_object())
-        .and_then(|obj| obj.get("flow"))
-        .and_then(|value| value.as_str())
-    {
-        return Ok(value.to_string());
+/// Whether `name` can be used as a flow name: it becomes a file name (`flows/<name>.ygtc`), so it
+/// must be a single path segment of ASCII letters, digits, `_` and `-`.
+pub fn is_valid_flow_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Reports a flow name taken from card data that is not [valid](is_valid_flow_name) and
+/// normalises it: runs of other characters become `-` and leading or trailing ones are dropped,
+/// so `a/b` becomes `a-b` and `../../x` becomes `x`.
+fn checked_flow_name(
+    flow: String,
+    rel_path: &str,
+    source: &SourceMap,
+    config: &ScanConfig,
+    warnings: &mut Vec<Warning>,
+    errors: &mut StrictErrors,
+) -> Result<String> {
+    if is_valid_flow_name(&flow) {
+        return Ok(flow);
     }
+    let segments: Vec<&str> = flow
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    let normalised = if segments.is_empty() {
+        "misc".to_string()
+    } else {
+        segments.join("-")
+    };
 
-    if config.group_by == Some(GroupBy::Folder)
-        && let Some(folder) = first_folder_component(rel_path)
-    {
-        return Ok(folder);
+    let message = format!(
+        "flow name {flow:?} in {rel_path} is not a single segment of letters, digits, '_' or '-'; \
+         using {normalised}"
+    );
+    config.policy().report(
+        warning(WarningKind::Inconsistent, message)
+            .at(source.root())
+            .rel_path(rel_path)
+            .flow(&normalised),
+        warnings,
+        errors,
+    );
+    Ok(normalised)
+}
+
+/// Applies an explicit `--group-by` strategy: its value wins, disagreeing metadata is reported,
+/// and cards the strategy cannot place fall back to other metadata, `--default-flow` or `misc`.
+/// Strict mode rejects both cases rather than mixing strategies.
+#[allow(clippy::too_many_arguments)]
+fn grouped_flow_name(
+    grouped: Option<String>,
+    strategy: &str,
+    others: &[(&str, Option<String>)],
+    rel_path: &str,
+    source: &SourceMap,
+    config: &ScanConfig,
+    warnings: &mut Vec<Warning>,
+    errors: &mut StrictErrors,
+) -> Result<String> {
+    if let Some(flow) = grouped {
+        for (origin, other) in others {
+            if let Some(other) = other
+                && other != &flow
+            {
+                let message = format!(
+                    "{origin} {other} in {rel_path} disagrees with {strategy} grouping ({flow})"
+                );
+                config.policy().report(
+                    warning(WarningKind::Inconsistent, message)
+                        .at(source.root())
+                        .rel_path(rel_path)
+                        .flow(&flow),
+                    warnings,
+                    errors,
+                );
+            }
+        }
+        return Ok(flow);
     }
 
-    if let Some(default_flow) = config.default_flow.as_ref() {
-        return Ok(default_flow.clone());
+    let fallback = others
+        .iter()
+        .find_map(|(source, value)| value.clone().map(|value| (*source, value)))
+        .or_else(|| {
+            config
+                .default_flow
+                .clone()
+                .map(|value| ("--default-flow", value))
+        });
+
+    let deny = config.policy().denies(WarningKind::MissingFlow);
+    if deny {
+        let message = match &fallback {
+            Some((source, _)) => format!(
+                "{rel_path} has no {strategy}; refusing to mix grouping strategies ({source})"
+            ),
+            None => format!("{rel_path} has no {strategy}"),
+        };
+        errors.push(
+            warning(WarningKind::MissingFlow, message)
+                .at(source.root())
+                .rel_path(rel_path),
+        );
     }
 
-    if config.strict {
-        bail!("unable to resolve flow name for {}", rel_path.display());
+    let (fallback_source, flow) = fallback.unwrap_or(("fallback", "misc".to_string()));
+    if !deny {
+        warnings.push(
+            warning(
+                WarningKind::MissingFlow,
+                format!("{rel_path} has no {strategy}; using {flow} from {fallback_source}"),
+            )
+            .at(source.root())
+            .rel_path(rel_path)
+            .flow(&flow),
+        );
     }
+    Ok(flow)
+}
 
-    warnings.push(warning(
-        WarningKind::MissingFlow,
-        format!("flow name missing for {}; using misc", rel_path.display()),
-    ));
-    Ok("misc".to_string())
+/// Walks the card depth-first and collects every action object with its JSON pointer: entries
+/// of any `actions` array (top level, `ActionSet`, `Action.ShowCard` sub-cards), every
+/// `selectAction`, and any other object typed `Action.*`. Action `data` is not descended into.
+fn discover_actions<'a>(
+    value: &'a Value,
+    path: String,
+    action_slot: bool,
+    found: &mut Vec<(String, &'a Value)>,
+    ignored: &mut Vec<String>,
+) {
+    match value {
+        Value::Object(map) => {
+            let is_action = action_slot
+                || map
+                    .get("type")
+                    .and_then(|value| value.as_str())
+                    .is_some_and(|kind| kind.starts_with("Action."));
+            if is_action {
+                found.push((path.clone(), value));
+            }
+            for (key, child) in map {
+                if is_action && key == "data" {
+                    continue;
+                }
+                let child_path = format!("{path}/{}", escape_pointer_token(key));
+                match (key.as_str(), child) {
+                    ("actions", Value::Array(items)) => {
+                        for (index, item) in items.iter().enumerate() {
+                            let item_path = format!("{child_path}/{index}");
+                            if item.is_object() {
+                                discover_actions(item, item_path, true, found, ignored);
+                            } else {
+                                ignored.push(item_path);
+                            }
+                        }
+                    }
+                    ("selectAction", Value::Object(_)) => {
+                        discover_actions(child, child_path, true, found, ignored);
+                    }
+                    _ => discover_actions(child, child_path, false, found, ignored),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                discover_actions(item, format!("{path}/{index}"), false, found, ignored);
+            }
+        }
+        _ => {}
+    }
 }
 
+fn pointer_str(value: &Value, pointer: &str) -> Option<String> {
+    value
+        .pointer(pointer)
+        .and_then(|value| value.as_str())
+        .map(|value| value.to_string())
+}
+
+fn first_pointer_str(value: &Value, pointers: &[String]) -> Option<String> {
+    pointers
+        .iter()
+        .find_map(|pointer| pointer_str(value, pointer))
+}
+
+/// Picks the value the card's actions agree on. `values` pairs each value with the pointer of
+/// the action it came from; a disagreement points at the first action that differs.
 fn resolve_consistent_value(
-    values: &[String],
+    values: &[(String, String)],
     label: &str,
     rel_path: &str,
-    strict: bool,
+    source: &SourceMap,
+    policy: SeverityPolicy<'_>,
     warnings: &mut Vec<Warning>,
+    errors: &mut StrictErrors,
 ) -> Result<Option<String>> {
-    if values.is_empty() {
+    let Some((first, _)) = values.first() else {
         return Ok(None);
-    }
+    };
 
-    let mut unique = values.to_vec();
+    let mut unique: Vec<&String> = values.iter().map(|(value, _)| value).collect();
     unique.sort();
     unique.dedup();
 
@@ -352,16 +1009,35 @@ fn resolve_consistent_value(
         let message = format!(
             "inconsistent {label} values in {}: {}",
             rel_path,
-            unique.join(", ")
+            unique
+                .iter()
+                .map(|value| value.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        let offending = values
+            .iter()
+            .find(|(value, _)| value != first)
+            .map_or("", |(_, pointer)| pointer.as_str());
+        policy.report(
+            warning(WarningKind::Inconsistent, message)
+                .at(source.locate(offending))
+                .rel_path(rel_path),
+            warnings,
+            errors,
         );
-        if strict {
-            bail!(message);
-        }
-        warnings.push(warning(WarningKind::Inconsistent, message));
-        return Ok(Some(values[0].clone()));
     }
 
-    Ok(Some(unique[0].clone()))
+    Ok(Some(first.clone()))
+}
+
+/// Location of a parse error, when the parser reports one.
+fn parse_error_location(path: &Path, err: &anyhow::Error) -> Location {
+    let file = path.display().to_string();
+    match error_position(err) {
+        Some((line, column)) => Location::position(file, line, column),
+        None => Location::file(file),
+    }
 }
 
 fn first_folder_component(rel_path: &Path) -> Option<String> {
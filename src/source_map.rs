@@ -0,0 +1,357 @@
+use std::collections::BTreeMap;
+
+use saphyr_parser::{Event, Parser};
+use serde_json::value::RawValue;
+
+use crate::card_source::{CardFormat, strip_jsonc};
+use crate::ir::Location;
+
+/// Line and column (1-based) of every value in a card source, keyed by JSON pointer. Object
+/// members point at their key, so `/greentic/cardId` lands on `"cardId": ...`.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    file: String,
+    positions: BTreeMap<String, (usize, usize)>,
+}
+
+impl SourceMap {
+    /// Maps an already parsed source. `file` is the path warnings print, e.g. `cards/a.json`.
+    pub fn new(file: impl Into<String>, contents: &str, format: CardFormat) -> Self {
+        let positions = match format {
+            CardFormat::Json => map_json(contents),
+            // Comments and trailing commas are blanked in place, so positions still match.
+            CardFormat::Jsonc => map_json(&strip_jsonc(contents)),
+            CardFormat::Yaml => map_yaml(contents),
+        };
+        Self {
+            file: file.into(),
+            positions,
+        }
+    }
+
+    /// A map for a file whose contents are unknown; every location is the bare file.
+    pub fn file_only(file: impl Into<String>) -> Self {
+        Self {
+            file: file.into(),
+            positions: BTreeMap::new(),
+        }
+    }
+
+    /// Location of the value at `pointer`, positioned at its nearest mapped ancestor when the
+    /// value itself is not mapped (e.g. inside a YAML flow collection).
+    pub fn locate(&self, pointer: &str) -> Location {
+        let mut current = pointer;
+        let position = loop {
+            if let Some(position) = self.positions.get(current) {
+                break Some(*position);
+            }
+            match current.rfind('/') {
+                Some(index) => current = &current[..index],
+                None => break None,
+            }
+        };
+        Location {
+            file: self.file.clone(),
+            line: position.map(|(line, _)| line),
+            column: position.map(|(_, column)| column),
+            pointer: Some(pointer.to_string()),
+        }
+    }
+
+    /// Location of the card as a whole (its root value).
+    pub fn root(&self) -> Location {
+        self.locate("")
+    }
+}
+
+impl Location {
+    /// A location naming only a file.
+    pub fn file(file: impl Into<String>) -> Self {
+        Self {
+            file: file.into(),
+            line: None,
+            column: None,
+            pointer: None,
+        }
+    }
+
+    /// A position reported by a parser, without a pointer.
+    pub fn position(file: impl Into<String>, line: usize, column: usize) -> Self {
+        Self {
+            file: file.into(),
+            line: Some(line),
+            column: Some(column),
+            pointer: None,
+        }
+    }
+}
+
+/// Where a JSON, JSONC or YAML parse error occurred, when the parser reports it.
+pub fn error_position(err: &anyhow::Error) -> Option<(usize, usize)> {
+    if let Some(err) = err.downcast_ref::<serde_json::Error>() {
+        return (err.line() > 0).then(|| (err.line(), err.column()));
+    }
+    err.downcast_ref::<serde_yaml_bw::Error>()
+        .and_then(serde_yaml_bw::Error::location)
+        .map(|location| (location.line(), location.column()))
+}
+
+/// Positions of the values in JSON text that is known to parse. serde_json hands out each value's
+/// raw text as a slice of the input, so positions are the parser's own (columns count bytes, as in
+/// its errors); object members are then moved back onto their key.
+fn map_json(text: &str) -> BTreeMap<String, (usize, usize)> {
+    let mut offsets = BTreeMap::new();
+    if let Ok(root) = serde_json::from_str::<&RawValue>(text) {
+        json_offsets(text, root, String::new(), None, &mut offsets);
+    }
+    offsets
+        .into_iter()
+        .map(|(pointer, offset)| (pointer, line_column(text, offset)))
+        .collect()
+}
+
+/// Records the byte offset of `raw` (or of its key, `key_offset`) and of everything inside it.
+fn json_offsets(
+    text: &str,
+    raw: &RawValue,
+    pointer: String,
+    key_offset: Option<usize>,
+    offsets: &mut BTreeMap<String, usize>,
+) {
+    let json = raw.get();
+    let offset = json.as_ptr() as usize - text.as_ptr() as usize;
+    offsets.insert(pointer.clone(), key_offset.unwrap_or(offset));
+    if json.starts_with('{') {
+        let members: BTreeMap<String, &RawValue> = serde_json::from_str(json).unwrap_or_default();
+        for (key, value) in members {
+            let value_offset = value.get().as_ptr() as usize - text.as_ptr() as usize;
+            let child = format!("{pointer}/{}", escape_pointer_token(&key));
+            json_offsets(
+                text,
+                value,
+                child,
+                json_key_offset(text, value_offset),
+                offsets,
+            );
+        }
+    } else if json.starts_with('[') {
+        let items: Vec<&RawValue> = serde_json::from_str(json).unwrap_or_default();
+        for (index, item) in items.into_iter().enumerate() {
+            json_offsets(text, item, format!("{pointer}/{index}"), None, offsets);
+        }
+    }
+}
+
+/// Offset of the opening quote of the key whose value starts at `value_offset`.
+fn json_key_offset(text: &str, value_offset: usize) -> Option<usize> {
+    let before = text[..value_offset].trim_end();
+    let before = before.strip_suffix(':')?.trim_end();
+    let closing = before.len().checked_sub(1)?;
+    let bytes = before.as_bytes();
+    (0..closing).rev().find(|&index| {
+        bytes[index] == b'"'
+            && bytes[..index]
+                .iter()
+                .rev()
+                .take_while(|byte| **byte == b'\\')
+                .count()
+                % 2
+                == 0
+    })
+}
+
+/// 1-based line and byte column of `offset` in `text`.
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text.as_bytes()[..offset];
+    let line = before.iter().filter(|byte| **byte == b'\n').count() + 1;
+    let line_start = before
+        .iter()
+        .rposition(|byte| *byte == b'\n')
+        .map_or(0, |index| index + 1);
+    (line, offset - line_start + 1)
+}
+
+/// Escapes an object key for use as one JSON pointer token (RFC 6901).
+pub fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Container being walked while mapping YAML events.
+enum YamlFrame {
+    /// A mapping and, once a key has been read, the pointer of its value (`None` for the value
+    /// of a complex key).
+    Mapping {
+        pointer: String,
+        value: Option<Option<String>>,
+    },
+    Sequence {
+        pointer: String,
+        next_index: usize,
+    },
+    /// A complex key (or the value of one), which has no pointer.
+    Unmapped,
+}
+
+/// Positions of the values in the first YAML document, from the spans `saphyr-parser` reports
+/// (columns count characters, as in YAML parse errors). Mapping entries point at their key.
+fn map_yaml(text: &str) -> BTreeMap<String, (usize, usize)> {
+    let mut positions = BTreeMap::new();
+    let mut frames: Vec<YamlFrame> = Vec::new();
+    let mut started = false;
+
+    for event in Parser::new_from_str(text) {
+        let Ok((event, span)) = event else {
+            break;
+        };
+        let position = (span.start.line(), span.start.col() + 1);
+        let opens = match event {
+            Event::Scalar(..) | Event::Alias(_) => false,
+            Event::MappingStart(..) | Event::SequenceStart(..) => true,
+            Event::MappingEnd | Event::SequenceEnd => {
+                frames.pop();
+                yaml_value_done(&mut frames);
+                continue;
+            }
+            Event::DocumentEnd if started => break,
+            _ => continue,
+        };
+        started = true;
+
+        // Where this node sits: a key, a value with a pointer, or inside a complex key.
+        let pointer = match frames.last_mut() {
+            None => {
+                positions.insert(String::new(), position);
+                Some(String::new())
+            }
+            Some(YamlFrame::Mapping { pointer, value }) => match value {
+                Some(value) => value.clone(),
+                None => {
+                    if let Event::Scalar(key, ..) = &event {
+                        let child = format!("{pointer}/{}", escape_pointer_token(key));
+                        positions.insert(child.clone(), position);
+                        *value = Some(Some(child));
+                        continue;
+                    }
+                    None
+                }
+            },
+            Some(YamlFrame::Sequence {
+                pointer,
+                next_index,
+            }) => {
+                let child = format!("{pointer}/{next_index}");
+                positions.insert(child.clone(), position);
+                Some(child)
+            }
+            Some(YamlFrame::Unmapped) => None,
+        };
+
+        if opens {
+            frames.push(match (pointer, event) {
+                (Some(pointer), Event::MappingStart(..)) => YamlFrame::Mapping {
+                    pointer,
+                    value: None,
+                },
+                (Some(pointer), _) => YamlFrame::Sequence {
+                    pointer,
+                    next_index: 0,
+                },
+                (None, _) => YamlFrame::Unmapped,
+            });
+        } else {
+            yaml_value_done(&mut frames);
+        }
+    }
+    positions
+}
+
+/// Advances the innermost container past a finished node: a mapping alternates between keys and
+/// values, a sequence moves to its next index.
+fn yaml_value_done(frames: &mut [YamlFrame]) {
+    match frames.last_mut() {
+        Some(YamlFrame::Mapping { value, .. }) => {
+            *value = match value {
+                None => Some(None),
+                Some(_) => None,
+            }
+        }
+        Some(YamlFrame::Sequence { next_index, .. }) => *next_index += 1,
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SourceMap;
+    use crate::card_source::CardFormat;
+
+    fn position(map: &SourceMap, pointer: &str) -> (Option<usize>, Option<usize>) {
+        let location = map.locate(pointer);
+        (location.line, location.column)
+    }
+
+    #[test]
+    fn maps_json_pointers_to_positions() {
+        let source = r#"{
+  "type": "AdaptiveCard",
+  "greentic": { "cardId": "a/b" },
+  "actions": [
+    { "type": "Action.Submit", "data": { "step": "next" } },
+    {
+      "type": "Action.Execute"
+    }
+  ]
+}"#;
+        let map = SourceMap::new("cards/a.json", source, CardFormat::Json);
+        assert_eq!(position(&map, ""), (Some(1), Some(1)));
+        assert_eq!(position(&map, "/greentic/cardId"), (Some(3), Some(17)));
+        assert_eq!(position(&map, "/actions/0"), (Some(5), Some(5)));
+        assert_eq!(position(&map, "/actions/0/data/step"), (Some(5), Some(42)));
+        assert_eq!(position(&map, "/actions/1/type"), (Some(7), Some(7)));
+        // Unknown pointers fall back to the nearest ancestor.
+        assert_eq!(position(&map, "/actions/1/verb"), (Some(6), Some(5)));
+        assert_eq!(map.locate("/actions/1").to_string(), "cards/a.json:6:5");
+
+        // Columns count bytes, like serde_json's errors; escaped keys are decoded.
+        let source = r#"{ "t\u00eftle": "é", "a\"/b": [1, { "x": 2 }] }"#;
+        let map = SourceMap::new("cards/b.json", source, CardFormat::Json);
+        assert_eq!(position(&map, "/t\u{ef}tle"), (Some(1), Some(3)));
+        assert_eq!(position(&map, "/a\"~1b"), (Some(1), Some(23)));
+        assert_eq!(position(&map, "/a\"~1b/1/x"), (Some(1), Some(38)));
+    }
+
+    #[test]
+    fn maps_yaml_pointers_to_positions() {
+        let source = "\
+type: AdaptiveCard
+greentic:
+  cardId: welcome
+body:
+- type: TextBlock
+  text: |
+    Hello
+    there
+- {type: Image, \"url/\\u00e9\": x}
+actions:
+  - type: Action.Submit
+    data:
+      step: next
+  - ? [complex, key]
+    : {ignored: true}
+    type: Action.Execute
+    verb: go
+";
+        let map = SourceMap::new("cards/a.yaml", source, CardFormat::Yaml);
+        assert_eq!(position(&map, ""), (Some(1), Some(1)));
+        assert_eq!(position(&map, "/greentic/cardId"), (Some(3), Some(3)));
+        assert_eq!(position(&map, "/body/0"), (Some(5), Some(3)));
+        assert_eq!(position(&map, "/body/0/text"), (Some(6), Some(3)));
+        assert_eq!(position(&map, "/body/1/type"), (Some(9), Some(4)));
+        assert_eq!(position(&map, "/body/1/url~1\u{e9}"), (Some(9), Some(17)));
+        assert_eq!(position(&map, "/actions"), (Some(10), Some(1)));
+        assert_eq!(position(&map, "/actions/0/type"), (Some(11), Some(5)));
+        assert_eq!(position(&map, "/actions/0/data/step"), (Some(13), Some(7)));
+        assert_eq!(position(&map, "/actions/1/type"), (Some(16), Some(5)));
+        assert_eq!(position(&map, "/actions/1/verb"), (Some(17), Some(5)));
+    }
+}
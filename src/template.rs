@@ -0,0 +1,331 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value};
+
+use crate::card_source::CardFormat;
+
+const DATA_SUFFIX: &str = ".data";
+const DATA_EXTENSIONS: [&str; 4] = ["json", "jsonc", "yaml", "yml"];
+
+/// Whether `path` is sample data for a template (`welcome.data.json` next to `welcome.json`).
+pub fn is_data_file(path: &Path) -> bool {
+    CardFormat::from_path(path).is_some()
+        && path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|stem| stem.ends_with(DATA_SUFFIX))
+}
+
+/// The sibling `<stem>.data.*` file for a card, if it is in the sorted card file list.
+pub fn data_file_for(card_path: &Path, files: &[PathBuf]) -> Option<PathBuf> {
+    let stem = card_path.file_stem()?.to_str()?;
+    DATA_EXTENSIONS
+        .iter()
+        .map(|extension| card_path.with_file_name(format!("{stem}{DATA_SUFFIX}.{extension}")))
+        .find(|candidate| files.binary_search(candidate).is_ok())
+}
+
+/// Stem of the card a data file belongs to (`welcome.data.json` -> `welcome`).
+pub fn card_stem_for_data(data_path: &Path) -> Option<&str> {
+    data_path.file_stem()?.to_str()?.strip_suffix(DATA_SUFFIX)
+}
+
+/// Simple `${path}` bindings in the card that the data never resolves. Expressions inside a
+/// `$data` scope or using functions are skipped because their binding depends on runtime context.
+pub fn unbound_variables(card: &Value, data: &Value) -> Vec<String> {
+    let mut variables = BTreeSet::new();
+    collect_variables(card, true, &mut variables);
+    variables
+        .into_iter()
+        .filter(|variable| lookup(data, variable).is_none())
+        .collect()
+}
+
+/// Expands `${...}` bindings against `data`. A string that is a single binding takes the bound
+/// value's type, `$data` arrays repeat their element, and unresolved expressions are kept.
+pub fn expand_template(card: &Value, data: &Value) -> Value {
+    expand(card, data, data)
+}
+
+fn collect_variables(value: &Value, root_scope: bool, variables: &mut BTreeSet<String>) {
+    match value {
+        Value::String(text) if root_scope => {
+            for expression in expressions(text) {
+                if let Some(path) = simple_path(expression) {
+                    variables.insert(path);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_variables(item, root_scope, variables);
+            }
+        }
+        Value::Object(map) => {
+            if let Some(scope) = map.get("$data") {
+                collect_variables(scope, root_scope, variables);
+            }
+            let scoped = root_scope && !map.contains_key("$data");
+            for (key, child) in map {
+                if key != "$data" {
+                    collect_variables(child, scoped, variables);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn expand(value: &Value, scope: &Value, root: &Value) -> Value {
+    match value {
+        Value::String(text) => expand_string(text, scope, root),
+        Value::Array(items) => {
+            let mut expanded = Vec::new();
+            for item in items {
+                match scoped_data(item, scope, root) {
+                    Some(Value::Array(entries)) => {
+                        expanded
+                            .extend(entries.iter().map(|entry| expand_object(item, entry, root)));
+                    }
+                    Some(entry) => expanded.push(expand_object(item, &entry, root)),
+                    None => expanded.push(expand(item, scope, root)),
+                }
+            }
+            Value::Array(expanded)
+        }
+        Value::Object(_) => match scoped_data(value, scope, root) {
+            Some(entry) => expand_object(value, &entry, root),
+            None => expand_object(value, scope, root),
+        },
+        other => other.clone(),
+    }
+}
+
+fn scoped_data(value: &Value, scope: &Value, root: &Value) -> Option<Value> {
+    let binding = value.as_object()?.get("$data")?;
+    match binding {
+        Value::String(text) => match expand_string(text, scope, root) {
+            Value::String(unresolved) if unresolved == *text => None,
+            resolved => Some(resolved),
+        },
+        other => Some(other.clone()),
+    }
+}
+
+fn expand_object(value: &Value, scope: &Value, root: &Value) -> Value {
+    let Some(map) = value.as_object() else {
+        return expand(value, scope, root);
+    };
+    let mut expanded = Map::new();
+    for (key, child) in map {
+        if key == "$data" {
+            continue;
+        }
+        expanded.insert(key.clone(), expand(child, scope, root));
+    }
+    Value::Object(expanded)
+}
+
+fn expand_string(text: &str, scope: &Value, root: &Value) -> Value {
+    let resolve = |expression: &str| {
+        let path = simple_path(expression)?;
+        let bound = match path.strip_prefix("$root") {
+            Some(rest) => lookup(root, rest.trim_start_matches('.')),
+            None => lookup(scope, &path),
+        };
+        bound.cloned()
+    };
+
+    let spans = bindings(text);
+    let trimmed_start = text.len() - text.trim_start().len();
+    if let [(start, end)] = spans[..]
+        && start == trimmed_start
+        && end + 1 == text.trim_end().len()
+        && let Some(bound) = resolve(&text[start + 2..end])
+    {
+        return bound;
+    }
+
+    let mut output = String::new();
+    let mut copied = 0;
+    for (start, end) in spans {
+        output.push_str(&text[copied..start]);
+        match resolve(&text[start + 2..end]) {
+            Some(Value::String(bound)) => output.push_str(&bound),
+            Some(bound) => output.push_str(&bound.to_string()),
+            None => output.push_str(&text[start..=end]),
+        }
+        copied = end + 1;
+    }
+    output.push_str(&text[copied..]);
+    Value::String(output)
+}
+
+fn expressions(text: &str) -> Vec<&str> {
+    bindings(text)
+        .into_iter()
+        .map(|(start, end)| &text[start + 2..end])
+        .collect()
+}
+
+/// Byte offsets of each `${` and its closing `}`. Braces nest and quoted strings are skipped, so
+/// `${if(a, '{x}', b)}` is one binding; an unclosed binding ends the search.
+fn bindings(text: &str) -> Vec<(usize, usize)> {
+    let mut found = Vec::new();
+    let mut from = 0;
+    while let Some(offset) = text[from..].find("${") {
+        let start = from + offset;
+        let Some(length) = closing_brace(&text[start + 2..]) else {
+            break;
+        };
+        let end = start + 2 + length;
+        found.push((start, end));
+        from = end + 1;
+    }
+    found
+}
+
+/// Offset of the `}` closing an expression that starts at the beginning of `expression`.
+fn closing_brace(expression: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut escaped = false;
+    for (index, c) in expression.char_indices() {
+        if let Some(open) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == open {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '\'' | '"' => quote = Some(c),
+            '{' => depth += 1,
+            '}' if depth == 0 => return Some(index),
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Normalises `name`, `a.b`, `a[0].b` and `$root.a` expressions; anything else (functions,
+/// operators, `$index`, `$host`) returns `None`.
+fn simple_path(expression: &str) -> Option<String> {
+    let expression = expression.trim();
+    let (prefix, body) = match expression.strip_prefix("$root") {
+        Some("") => return None,
+        Some(rest) => ("$root.", rest.strip_prefix('.')?),
+        None => ("", expression),
+    };
+    let valid = !body.is_empty()
+        && body.split('.').all(|segment| {
+            let name = segment.split('[').next().unwrap_or_default();
+            let mut chars = name.chars();
+            chars
+                .next()
+                .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+                && chars.all(|next| next.is_ascii_alphanumeric() || next == '_')
+                && segment[name.len()..].split_terminator(']').all(|index| {
+                    index.strip_prefix('[').is_some_and(|digits| {
+                        !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+                    })
+                })
+        });
+    valid.then(|| format!("{prefix}{body}"))
+}
+
+fn lookup<'a>(data: &'a Value, path: &str) -> Option<&'a Value> {
+    let path = path.strip_prefix("$root.").unwrap_or(path);
+    if path.is_empty() {
+        return Some(data);
+    }
+    let mut current = data;
+    for segment in path.split('.') {
+        let mut parts = segment.split('[');
+        let name = parts.next()?;
+        current = current.get(name)?;
+        for index in parts {
+            let index: usize = index.strip_suffix(']')?.parse().ok()?;
+            current = current.get(index)?;
+        }
+    }
+    Some(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand_template, unbound_variables};
+    use serde_json::json;
+
+    #[test]
+    fn reports_unbound_variables_outside_data_scopes() {
+        let card = json!({
+            "type": "AdaptiveCard",
+            "body": [
+                { "type": "TextBlock", "text": "Hi ${user.name}, ${greeting} ${if(x, 'a', 'b')}" },
+                { "type": "Container", "$data": "${items}", "items": [{ "text": "${title}" }] },
+                { "type": "TextBlock", "text": "${$root.missing[0].value}" }
+            ]
+        });
+        let data = json!({ "user": { "name": "Ada" }, "items": [] });
+        assert_eq!(
+            unbound_variables(&card, &data),
+            vec!["$root.missing[0].value", "greeting"]
+        );
+    }
+
+    #[test]
+    fn expands_bindings_and_repeats_data_arrays() {
+        let card = json!({
+            "type": "AdaptiveCard",
+            "body": [
+                { "type": "TextBlock", "text": "Hi ${user.name} (${unknown})" },
+                { "type": "TextBlock", "$data": "${items}", "text": "${title} of ${$root.user.name}" }
+            ],
+            "actions": [{ "type": "Action.Submit", "data": { "count": "${count}" } }]
+        });
+        let data = json!({
+            "user": { "name": "Ada" },
+            "count": 2,
+            "items": [{ "title": "one" }, { "title": "two" }]
+        });
+        assert_eq!(
+            expand_template(&card, &data),
+            json!({
+                "type": "AdaptiveCard",
+                "body": [
+                    { "type": "TextBlock", "text": "Hi Ada (${unknown})" },
+                    { "type": "TextBlock", "text": "one of Ada" },
+                    { "type": "TextBlock", "text": "two of Ada" }
+                ],
+                "actions": [{ "type": "Action.Submit", "data": { "count": 2 } }]
+            })
+        );
+    }
+
+    #[test]
+    fn matches_braces_inside_expressions() {
+        let card = json!({
+            "type": "AdaptiveCard",
+            "body": [
+                { "type": "TextBlock", "text": "${if(a, '{x}', b)} and ${name}" },
+                { "type": "TextBlock", "text": "${if(a, '}${user}', \"{\")} ${missing}" }
+            ]
+        });
+        let data = json!({ "name": "Ada" });
+        assert_eq!(unbound_variables(&card, &data), vec!["missing"]);
+        assert_eq!(
+            expand_template(&card, &data)["body"][0]["text"],
+            "${if(a, '{x}', b)} and Ada"
+        );
+        assert_eq!(
+            expand_template(&card, &data)["body"][1]["text"],
+            card["body"][1]["text"]
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use std::env;
+use std::io;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
 
 use anyhow::{Context, Result, bail};
 
@@ -27,6 +28,7 @@ pub fn run_greentic_pack_new(bin: &Path, out_dir: &Path, name: &str) -> Result<(
         .arg("--dir")
         .arg(out_dir)
         .arg(name)
+        .stdout(Stdio::from(io::stderr()))
         .status()
         .with_context(|| format!("failed to run greentic-pack new for {}", out_dir.display()))?;
 
@@ -42,6 +44,7 @@ pub fn run_greentic_pack_doctor(bin: &Path, workspace: &Path) -> Result<()> {
         .arg("doctor")
         .arg("--in")
         .arg(workspace)
+        .stdout(Stdio::from(io::stderr()))
         .status()
         .with_context(|| {
             format!(
@@ -62,6 +65,7 @@ pub fn run_greentic_pack_update(bin: &Path, workspace: &Path) -> Result<()> {
         .arg("update")
         .arg("--in")
         .arg(workspace)
+        .stdout(Stdio::from(io::stderr()))
         .status()
         .with_context(|| {
             format!(
@@ -82,6 +86,7 @@ pub fn run_greentic_pack_components(bin: &Path, workspace: &Path) -> Result<()>
         .arg("components")
         .arg("--in")
         .arg(workspace)
+        .stdout(Stdio::from(io::stderr()))
         .status()
         .with_context(|| {
             format!(
@@ -105,6 +110,7 @@ pub fn run_greentic_pack_resolve(bin: &Path, workspace: &Path) -> Result<()> {
         .arg("resolve")
         .arg("--in")
         .arg(workspace)
+        .stdout(Stdio::from(io::stderr()))
         .status()
         .with_context(|| {
             format!(
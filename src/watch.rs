@@ -0,0 +1,148 @@
+use std::collections::BTreeMap;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::{Result, bail};
+use walkdir::WalkDir;
+
+use crate::cli::OutputFormat;
+use crate::config::GenerateOptions;
+use crate::workspace::{generate_workspace, print_result};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+type Snapshot = BTreeMap<PathBuf, (Option<SystemTime>, u64)>;
+
+pub fn watch(options: &GenerateOptions, debounce_ms: u64, format: OutputFormat) -> Result<()> {
+    if !options.cards.is_dir() {
+        bail!(
+            "cards directory does not exist: {}",
+            options.cards.display()
+        );
+    }
+    if options.prompt && options.prompt_json.is_none() {
+        bail!("--watch with --prompt requires --prompt-json");
+    }
+
+    let debounce = Duration::from_millis(debounce_ms);
+    let mut build_requests = Some(spawn_build_requests());
+    eprintln!(
+        "Watching {} (press Enter to build the .gtpack, Ctrl-C to stop)",
+        options.cards.display()
+    );
+
+    run_once(options, false, format);
+    let mut snapshot = snapshot_cards(&options.cards, &options.out);
+    let mut pending: Option<Instant> = None;
+
+    loop {
+        let build_requested = match build_requests.as_ref() {
+            Some(receiver) => match receiver.recv_timeout(POLL_INTERVAL) {
+                Ok(()) => true,
+                Err(RecvTimeoutError::Timeout) => false,
+                Err(RecvTimeoutError::Disconnected) => {
+                    build_requests = None;
+                    false
+                }
+            },
+            None => {
+                thread::sleep(POLL_INTERVAL);
+                false
+            }
+        };
+
+        if build_requested {
+            run_once(options, true, format);
+            snapshot = snapshot_cards(&options.cards, &options.out);
+            pending = None;
+            continue;
+        }
+
+        let current = snapshot_cards(&options.cards, &options.out);
+        if current != snapshot {
+            snapshot = current;
+            pending = Some(Instant::now());
+        }
+
+        if pending.is_some_and(|changed| changed.elapsed() >= debounce) {
+            pending = None;
+            run_once(options, false, format);
+            snapshot = snapshot_cards(&options.cards, &options.out);
+        }
+    }
+}
+
+fn run_once(options: &GenerateOptions, build_pack: bool, format: OutputFormat) {
+    if build_pack {
+        eprintln!("Regenerating workspace and building .gtpack...");
+    } else {
+        eprintln!("Regenerating workspace...");
+    }
+    match generate_workspace(options, build_pack) {
+        Ok(manifest) => {
+            if let Err(err) = print_result(&manifest, format) {
+                eprintln!("failed to print result: {err:#}");
+            }
+        }
+        Err(err) => eprintln!("generate failed: {err:#}"),
+    }
+}
+
+fn spawn_build_requests() -> Receiver<()> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            if line.is_err() || sender.send(()).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+fn snapshot_cards(cards_dir: &Path, out_dir: &Path) -> Snapshot {
+    let mut snapshot = Snapshot::new();
+    for entry in WalkDir::new(cards_dir).into_iter().filter_map(Result::ok) {
+        if !entry.file_type().is_file() || entry.path().starts_with(out_dir) {
+            continue;
+        }
+        let metadata = entry.metadata().ok();
+        snapshot.insert(
+            entry.path().to_path_buf(),
+            (
+                metadata.as_ref().and_then(|meta| meta.modified().ok()),
+                metadata.map(|meta| meta.len()).unwrap_or(0),
+            ),
+        );
+    }
+    snapshot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::snapshot_cards;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn snapshot_detects_changes_and_skips_out_dir() {
+        let tmp = TempDir::new().expect("temp dir");
+        let out = tmp.path().join("workspace");
+        fs::create_dir_all(&out).expect("out dir");
+        fs::write(tmp.path().join("card.json"), "{}").expect("write card");
+        fs::write(out.join("generated.json"), "{}").expect("write generated");
+
+        let before = snapshot_cards(tmp.path(), &out);
+        assert_eq!(before.len(), 1);
+
+        fs::write(
+            tmp.path().join("card.json"),
+            "{ \"type\": \"AdaptiveCard\" }",
+        )
+        .expect("rewrite card");
+        assert_ne!(snapshot_cards(tmp.path(), &out), before);
+    }
+}
@@ -1,21 +1,36 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::env;
 use std::fs;
-use std::io::Write;
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
+use std::process::Stdio;
 
 use anyhow::{Context, Result, anyhow, bail};
 use walkdir::WalkDir;
 
-use crate::cli::GenerateArgs;
-use crate::diagnostics::{build_diagnostics, summarize, warning};
+use crate::card_files::{CardFilter, list_card_files};
+use crate::card_source::{CardFormat, asset_rel_path, normalized_json};
+use crate::cli::{GenerateArgs, OutputFormat};
+use crate::config::{GenerateOptions, resolve_generate};
+use crate::diagnostics::{
+    SeverityPolicy, StrictErrors, StrictFailure, build_diagnostics, summarize, warning,
+};
 use crate::emit_flow::emit_flow;
-use crate::graph::build_flow_graph;
-use crate::ir::{FlowSummary, Manifest, Warning, WarningKind};
+use crate::graph::{FlowGraph, build_flow_graph_collecting};
+use crate::incremental::{
+    BuildState, FlowState, PackState, card_hashes, file_hash, flow_fingerprint,
+    options_fingerprint, workspace_fingerprint,
+};
+use crate::inspect::write_graphs;
+use crate::ir::{FlowSummary, GenerateReport, Location, Manifest, Warning, WarningKind};
+use crate::manifest::read_manifest;
+use crate::parallel::{map_ordered, worker_count};
 use crate::qa_integration::{
     PromptLimits, Source, build_prompt2flow_config, persist_prompt2flow_config,
-    prompt_limits_from_arg,
 };
-use crate::scan::{ScanConfig, scan_cards};
+use crate::sarif::sarif_log;
+use crate::scan::{ScanConfig, scan_card_files};
+use crate::template::{data_file_for, expand_template, is_data_file};
 use crate::tools::{
     resolve_greentic_pack_bin, run_greentic_pack_build, run_greentic_pack_components,
     run_greentic_pack_doctor, run_greentic_pack_new, run_greentic_pack_resolve,
@@ -30,31 +45,82 @@ const PROMPT_COMPONENT_REF: &str =
     "oci://ghcr.io/greentic-ai/components/component-prompt2flow:latest";
 
 pub fn generate(args: &GenerateArgs) -> Result<()> {
-    if !args.cards.is_dir() {
-        bail!("cards directory does not exist: {}", args.cards.display());
+    let options = resolve_generate(args)?;
+    if args.watch {
+        return crate::watch::watch(&options, args.debounce_ms, args.format);
+    }
+    if args.dry_run {
+        return crate::plan::dry_run(&options, args.format);
     }
 
-    if args.prompt_json.is_some() && !args.prompt {
-        bail!("--prompt-json requires --prompt");
+    let result = generate_workspace(&options, true);
+    if let Some(path) = args.sarif.as_deref() {
+        write_sarif_for(path, result.as_ref())?;
     }
-    if args.prompt_limits.is_some() && !args.prompt {
-        bail!("--prompt-limits requires --prompt");
+    print_result(&result?, args.format)
+}
+
+pub fn print_result(manifest: &Manifest, format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Text => println!("{}", summarize(&manifest.diagnostics, &manifest.warnings)),
+        OutputFormat::Json => {
+            let report = GenerateReport {
+                manifest_path: manifest
+                    .diagnostics
+                    .workspace_root
+                    .join(".cards2pack")
+                    .join("manifest.json"),
+                diagnostics: manifest.diagnostics.clone(),
+                warnings: manifest.warnings.clone(),
+            };
+            let json =
+                serde_json::to_string_pretty(&report).context("serialize generate report")?;
+            println!("{json}");
+        }
     }
+    Ok(())
+}
+
+/// Runs the scan, graph and emit pipeline into `--out`; the `greentic-pack build` step only runs
+/// when `build_pack` is set.
+pub fn generate_workspace(options: &GenerateOptions, build_pack: bool) -> Result<Manifest> {
+    generate_workspace_for(options, build_pack, &options.out)
+}
 
-    let greentic_pack_bin = resolve_greentic_pack_bin(args.greentic_pack_bin.as_deref())?;
-    let pack_yaml = args.out.join("pack.yaml");
+/// Like [`generate_workspace`], but writes into `options.out` a workspace that will live at
+/// `final_out`, e.g. a dry run's scratch copy. Paths the workspace records relative to itself are
+/// computed against `final_out`.
+pub fn generate_workspace_for(
+    options: &GenerateOptions,
+    build_pack: bool,
+    final_out: &Path,
+) -> Result<Manifest> {
+    if !options.cards.is_dir() {
+        bail!(
+            "cards directory does not exist: {}",
+            options.cards.display()
+        );
+    }
+
+    let greentic_pack_bin = resolve_greentic_pack_bin(options.greentic_pack_bin.as_deref())?;
+    let pack_yaml = options.out.join("pack.yaml");
+    // Flow files cards2pack owns: those the previous run emitted and, in a new workspace, the ones
+    // `greentic-pack new` scaffolds. Any not emitted this run are removed.
+    let mut previous_warnings = Vec::new();
+    let mut owned_flows = previous_flow_files(&options.out, &mut previous_warnings)?;
     if !pack_yaml.exists() {
-        run_greentic_pack_new(&greentic_pack_bin, &args.out, &args.name)?;
+        run_greentic_pack_new(&greentic_pack_bin, &options.out, &options.name)?;
+        owned_flows.extend(flow_files(&options.out.join("flows")));
     }
     let default_flow_path = default_flow_file(&pack_yaml)?;
 
-    fs::create_dir_all(&args.out)
-        .with_context(|| format!("failed to create workspace {}", args.out.display()))?;
+    fs::create_dir_all(&options.out)
+        .with_context(|| format!("failed to create workspace {}", options.out.display()))?;
 
-    let assets_cards = args.out.join("assets").join("cards");
-    let flows_dir = args.out.join("flows");
-    let dist_dir = args.out.join("dist");
-    let state_dir = args.out.join(".cards2pack");
+    let assets_cards = options.out.join("assets").join("cards");
+    let flows_dir = options.out.join("flows");
+    let dist_dir = options.out.join("dist");
+    let state_dir = options.out.join(".cards2pack");
 
     fs::create_dir_all(&assets_cards)
         .with_context(|| format!("failed to create {}", assets_cards.display()))?;
@@ -65,23 +131,35 @@ pub fn generate(args: &GenerateArgs) -> Result<()> {
     fs::create_dir_all(&state_dir)
         .with_context(|| format!("failed to create {}", state_dir.display()))?;
 
-    copy_cards(&args.cards, &assets_cards)?;
-    ensure_readme(&args.out, &args.name)?;
-
-    let prompt_limits = if args.prompt {
-        prompt_limits_from_arg(args.prompt_limits.as_deref())?.unwrap_or_default()
+    let card_filter = CardFilter::load(&options.cards, &options.include, &options.exclude)?;
+    let card_files = list_card_files(&options.cards, &card_filter)?;
+    let mut build_state =
+        BuildState::load(&state_dir, &options_fingerprint(options)?, options.force);
+    let card_hashes = card_hashes(&options.cards, &card_files)?;
+    let mut card_assets = copy_cards(
+        &options.cards,
+        &card_files,
+        &assets_cards,
+        options.expand_templates,
+        &build_state.changed_cards(&card_hashes),
+    )?;
+    build_state.cards = card_hashes;
+    ensure_readme(&options.out, &options.name)?;
+
+    let prompt_limits = if options.prompt {
+        options.prompt_limits.unwrap_or_default()
     } else {
         PromptLimits::default()
     };
 
-    if args.prompt {
-        let source = args
+    if options.prompt {
+        let source = options
             .prompt_json
             .as_deref()
             .map(Source::JsonFile)
             .unwrap_or(Source::Interactive);
         let config = build_prompt2flow_config(source, prompt_limits)?;
-        let prompt_config_path = args
+        let prompt_config_path = options
             .out
             .join("assets")
             .join("config")
@@ -90,107 +168,222 @@ pub fn generate(args: &GenerateArgs) -> Result<()> {
     }
 
     let scan_config = ScanConfig {
-        cards_dir: assets_cards.clone(),
-        group_by: args.group_by,
-        default_flow: args.default_flow.clone(),
-        strict: args.strict,
+        cards_dir: options.cards.clone(),
+        group_by: options.group_by,
+        flow_field: options.flow_field.clone(),
+        default_flow: options.default_flow.clone(),
+        strict: options.strict,
+        routing_keys: options.routing_keys.clone(),
+        include: options.include.clone(),
+        exclude: options.exclude.clone(),
+        jobs: options.jobs,
+        host: options.host,
+        max_errors: options.max_errors,
+        severities: options.severities.clone(),
+        expand_templates: options.expand_templates,
+        locale_suffixes: options.locale_suffixes,
     };
-    let mut manifest = scan_cards(&scan_config)?;
+    let policy = SeverityPolicy {
+        strict: options.strict,
+        overrides: &options.severities,
+    };
+    // Emission drops routes that close a cycle; denying `inconsistent` makes that an error.
+    let strict_emit = policy.denies(WarningKind::Inconsistent);
+    // Strict-mode errors from scanning, default-flow selection and graph building are reported
+    // together before anything is emitted.
+    let mut strict_errors = StrictErrors::new(options.max_errors);
+    let mut manifest = scan_card_files(&scan_config, &card_files, &mut strict_errors)?;
+    manifest.warnings.extend(previous_warnings);
+
+    let flow_names: Vec<String> = manifest
+        .flows
+        .iter()
+        .map(|flow| flow.flow_name.clone())
+        .collect();
+    let default_flow = select_default_flow(
+        &flow_names,
+        options.default_flow.as_deref(),
+        default_flow_path.as_deref(),
+        policy,
+        &mut manifest.warnings,
+        &mut strict_errors,
+    )?;
+    policy.apply(&mut manifest.warnings, &mut strict_errors);
+
+    let mut planned = Vec::new();
+    for flow in &manifest.flows {
+        let graph = build_flow_graph_collecting(flow, policy, &mut strict_errors)?;
+        let is_prompt_flow =
+            options.prompt && default_flow.as_deref() == Some(flow.flow_name.as_str());
+        let fingerprint = flow_fingerprint(&graph, is_prompt_flow, strict_emit);
+        let path = flows_dir.join(format!("{}.ygtc", flow.flow_name));
+        let previous = build_state
+            .unchanged_flow(&flow.flow_name, &fingerprint, &path)
+            .cloned();
+        planned.push(PlannedFlow {
+            graph,
+            is_prompt_flow,
+            fingerprint,
+            previous,
+        });
+    }
+    if !strict_errors.is_empty() {
+        let graph_warnings = planned.iter().flat_map(|flow| &flow.graph.warnings);
+        let warnings: Vec<Warning> = manifest
+            .warnings
+            .iter()
+            .chain(graph_warnings)
+            .cloned()
+            .collect();
+        strict_errors.finish_with(&warnings)?;
+    }
+    card_assets.extend(write_input_schemas(&manifest, &assets_cards)?);
+    remove_stale_assets(&assets_cards, &card_assets)?;
+
+    // Flows are independent, so each one's greentic-flow calls run on their own worker; results
+    // are consumed in flow order below.
+    let emitted = map_ordered(
+        &planned,
+        worker_count(options.jobs),
+        |flow| -> Result<FlowState> {
+            if let Some(previous) = &flow.previous {
+                return Ok(previous.clone());
+            }
+            let (path, flow_warnings) = emit_flow(&flow.graph, &options.out, strict_emit)?;
+            if flow.is_prompt_flow {
+                insert_prompt_node(&path)?;
+            }
+            Ok(FlowState {
+                fingerprint: flow.fingerprint.clone(),
+                file: file_hash(&path).unwrap_or_default(),
+                warnings: flow_warnings,
+            })
+        },
+    );
 
+    let mut emitted_flows = BTreeSet::new();
     let mut flow_paths = Vec::new();
     let mut readme_entries = Vec::new();
-    for flow in &manifest.flows {
-        let graph = build_flow_graph(flow, args.strict)?;
+    let mut registered_flows = Vec::new();
+    let mut graphs = Vec::new();
+    let mut flow_states = BTreeMap::new();
+    for (flow, flow_state) in planned.into_iter().zip(emitted) {
+        let flow_state = flow_state?;
+        let graph = flow.graph;
         if !graph.warnings.is_empty() {
             manifest.warnings.extend(graph.warnings.iter().cloned());
         }
-        let (path, flow_warnings) = emit_flow(&graph, &args.out, args.strict)?;
-        if !flow_warnings.is_empty() {
-            manifest.warnings.extend(flow_warnings);
-        }
-        let is_prompt_flow = args.prompt
-            && default_flow_path
-                .as_ref()
-                .map(|default| default == &path)
-                .unwrap_or(false);
-        if is_prompt_flow {
-            insert_prompt_node(&path)?;
-        }
-        write_flow_resolve_sidecar(&path, &graph)?;
-        if is_prompt_flow {
+        manifest
+            .warnings
+            .extend(flow_state.warnings.iter().cloned());
+        let path = flows_dir.join(format!("{}.ygtc", graph.flow_name));
+        emitted_flows.insert(path.clone());
+        write_flow_resolve_sidecar(&path, &final_out.join("flows"), &graph)?;
+        if flow.is_prompt_flow {
             extend_sidecar_with_prompt(&path)?;
         }
         let flow_path = path
-            .strip_prefix(&args.out)
+            .strip_prefix(&options.out)
             .unwrap_or(&path)
             .display()
             .to_string();
         if !flow_paths.contains(&flow_path) {
+            registered_flows.push((graph.flow_name.clone(), flow_path.replace('\\', "/")));
             flow_paths.push(flow_path);
         }
         let entry = graph
-            .nodes
-            .values()
-            .find(|node| !node.stub)
+            .entry_node()
             .map(|node| node.name.clone())
             .unwrap_or_else(|| "unknown".to_string());
-        readme_entries.push((flow.flow_name.clone(), entry));
+        readme_entries.push((graph.flow_name.clone(), entry));
+        flow_states.insert(graph.flow_name.clone(), flow_state);
+        graphs.push(graph);
     }
+    build_state.flows = flow_states;
 
-    sync_local_component_if_configured(&args.out, &greentic_pack_bin, &mut manifest, args.strict)?;
-    run_greentic_pack_update(&greentic_pack_bin, &args.out)?;
-    update_readme(&args.out, &args.name, &readme_entries)?;
+    // Emission warnings (unrouted cards, missing-target stubs, omitted cycle routes) must fail
+    // the run before any pack step writes to `dist/`.
+    let mut denied = StrictErrors::new(options.max_errors);
+    policy.apply(&mut manifest.warnings, &mut denied);
+    denied.finish_with(&manifest.warnings)?;
 
-    if let Err(err) = run_greentic_flow_doctor(&args.out.join("flows")) {
-        if args.strict {
-            return Err(err);
-        }
-        manifest.warnings.push(warning(
-            WarningKind::Validation,
-            format!("greentic-flow doctor failed: {err}"),
-        ));
+    if let Some(format) = options.graph {
+        write_graphs(&graphs, &state_dir.join("graphs"), format)?;
     }
+    // Deleting the files also drops their pack.yaml entries (see `register_flows_in_pack_yaml`).
+    remove_stale_flows(owned_flows.difference(&emitted_flows))?;
+
+    sync_local_component_if_configured(&options.out, &greentic_pack_bin, &mut manifest, policy)?;
+    let gtpack_out = dist_dir.join(format!("{}.gtpack", options.name));
+    let unchanged_pack = if build_pack {
+        build_state
+            .unchanged_pack(&workspace_fingerprint(&options.out)?, &gtpack_out)
+            .cloned()
+    } else {
+        None
+    };
+    let gtpack_path = if let Some(previous) = unchanged_pack {
+        eprintln!(
+            "No changes since the last build; keeping {} (use --force to rebuild)",
+            gtpack_out.display()
+        );
+        manifest.warnings.extend(previous.warnings);
+        Some(gtpack_out)
+    } else {
+        let pack_warnings_start = manifest.warnings.len();
+        run_greentic_pack_update(&greentic_pack_bin, &options.out)?;
+        register_flows_in_pack_yaml(&pack_yaml, &registered_flows, default_flow.as_deref())?;
+        update_readme(&options.out, &options.name, &readme_entries)?;
 
-    if let Err(err) = run_greentic_pack_resolve(&greentic_pack_bin, &args.out) {
-        if args.strict {
-            return Err(err);
+        if let Err(err) = run_greentic_flow_doctor(&options.out.join("flows")) {
+            report_validation(policy, &mut manifest, "greentic-flow doctor", &err)?;
         }
-        manifest.warnings.push(warning(
-            WarningKind::Validation,
-            format!("greentic-pack resolve failed: {err}"),
-        ));
-    }
-
-    if let Err(err) = run_greentic_pack_doctor(&greentic_pack_bin, &args.out) {
-        if args.strict {
-            return Err(err);
+        if let Err(err) = run_greentic_pack_resolve(&greentic_pack_bin, &options.out) {
+            report_validation(policy, &mut manifest, "greentic-pack resolve", &err)?;
+        }
+        if let Err(err) = run_greentic_pack_doctor(&greentic_pack_bin, &options.out) {
+            report_validation(policy, &mut manifest, "greentic-pack doctor", &err)?;
         }
-        manifest.warnings.push(warning(
-            WarningKind::Validation,
-            format!("greentic-pack doctor failed: {err}"),
-        ));
-    }
 
-    let gtpack_out = dist_dir.join(format!("{}.gtpack", args.name));
-    let build_output =
-        run_greentic_pack_build(&greentic_pack_bin, &args.out, &gtpack_out, args.verbose)?;
-    if !gtpack_out.exists()
-        && let Some(path) = extract_gtpack_path(&build_output)
-        && path.exists()
-    {
-        fs::copy(&path, &gtpack_out).with_context(|| {
-            format!(
-                "failed to copy greentic-pack output {} to {}",
-                path.display(),
-                gtpack_out.display()
-            )
-        })?;
-    }
+        if build_pack {
+            let build_output = run_greentic_pack_build(
+                &greentic_pack_bin,
+                &options.out,
+                &gtpack_out,
+                options.verbose,
+            )?;
+            if !gtpack_out.exists()
+                && let Some(path) = extract_gtpack_path(&build_output)
+                && path.exists()
+            {
+                fs::copy(&path, &gtpack_out).with_context(|| {
+                    format!(
+                        "failed to copy greentic-pack output {} to {}",
+                        path.display(),
+                        gtpack_out.display()
+                    )
+                })?;
+            }
 
-    let (gtpack_path, gtpack_warning) = ensure_named_gtpack(&dist_dir, &args.name)?;
-    if let Some(warning) = gtpack_warning {
-        manifest.warnings.push(warning);
-    }
+            let (gtpack_path, gtpack_warning) = ensure_named_gtpack(&dist_dir, &options.name)?;
+            if let Some(warning) = gtpack_warning {
+                manifest.warnings.push(warning);
+            }
+            build_state.pack = Some(PackState {
+                workspace: workspace_fingerprint(&options.out)?,
+                artifact: file_hash(&gtpack_path).unwrap_or_default(),
+                warnings: manifest.warnings[pack_warnings_start..].to_vec(),
+            });
+            Some(gtpack_path)
+        } else {
+            None
+        }
+    };
+
+    // Pack warnings; everything earlier was checked before the pack steps.
+    let mut denied = StrictErrors::new(options.max_errors);
+    policy.apply(&mut manifest.warnings, &mut denied);
+    denied.finish_with(&manifest.warnings)?;
 
     let flow_summaries: Vec<FlowSummary> = manifest
         .flows
@@ -201,42 +394,251 @@ pub fn generate(args: &GenerateArgs) -> Result<()> {
         })
         .collect();
     manifest.diagnostics = build_diagnostics(
-        args.out.clone(),
-        Some(gtpack_path.clone()),
+        options.out.clone(),
+        gtpack_path,
         flow_paths.clone(),
         flow_summaries,
         manifest.flows.iter().map(|flow| flow.cards.len()).sum(),
-        manifest.warnings.len(),
+        &manifest.warnings,
     );
+    manifest.config = Some(options.clone());
     write_manifest(&state_dir, &manifest)?;
+    build_state.save(&state_dir)?;
 
-    println!("{}", summarize(&manifest.diagnostics, &manifest.warnings));
+    Ok(manifest)
+}
 
-    Ok(())
+/// Records a failed greentic validation step; when `validation` is denied the run stops here.
+fn report_validation(
+    policy: SeverityPolicy<'_>,
+    manifest: &mut Manifest,
+    tool: &str,
+    err: &anyhow::Error,
+) -> Result<()> {
+    let mut denied = StrictErrors::default();
+    policy.report(
+        warning(WarningKind::Validation, format!("{tool} failed: {err}")).tool(tool),
+        &mut manifest.warnings,
+        &mut denied,
+    );
+    denied.finish_with(&manifest.warnings)
 }
 
-fn copy_cards(cards_dir: &Path, dest_root: &Path) -> Result<()> {
-    for entry in WalkDir::new(cards_dir).into_iter().filter_map(Result::ok) {
-        if !entry.file_type().is_file() {
-            continue;
-        }
+/// A flow graph and whether its previous emission can be reused.
+struct PlannedFlow {
+    graph: FlowGraph,
+    is_prompt_flow: bool,
+    fingerprint: String,
+    /// Set when the flow is unchanged since the last run.
+    previous: Option<FlowState>,
+}
 
-        let path = entry.path();
-        let extension = path.extension().and_then(|ext| ext.to_str());
-        if extension.is_none_or(|ext| !ext.eq_ignore_ascii_case("json")) {
+/// Copies JSON cards into `assets/cards` and writes YAML/JSONC cards there as canonical JSON.
+/// With `expand_templates`, cards with sample data are written with their bindings expanded and
+/// the `<stem>.data.*` files themselves are not packaged.
+/// Sources that fail to parse are skipped here and left out of the result, so an asset they
+/// produced earlier is removed; the scan reports them. Sources not in `changed` (paths under
+/// `cards_dir`) whose asset already exists are left alone. Returns every asset path (relative to
+/// `dest_root`) that belongs to a parseable card in `card_files`.
+fn copy_cards(
+    cards_dir: &Path,
+    card_files: &[PathBuf],
+    dest_root: &Path,
+    expand_templates: bool,
+    changed: &BTreeSet<String>,
+) -> Result<BTreeSet<PathBuf>> {
+    let mut sources: BTreeMap<PathBuf, PathBuf> = BTreeMap::new();
+    let mut written = BTreeSet::new();
+    for path in card_files {
+        let path = path.as_path();
+        let Some(format) = CardFormat::from_path(path) else {
+            continue;
+        };
+        if expand_templates && is_data_file(path) {
             continue;
         }
 
         let rel = path
             .strip_prefix(cards_dir)
             .with_context(|| format!("failed to strip prefix for {}", path.display()))?;
-        let dest_path = dest_root.join(rel);
+        let asset_rel = asset_rel_path(rel);
+        if let Some(previous) = sources.insert(asset_rel.clone(), rel.to_path_buf()) {
+            bail!(
+                "{} and {} both map to assets/cards/{}",
+                previous.display(),
+                rel.display(),
+                asset_rel.display()
+            );
+        }
+
+        let contents =
+            fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+        let Ok(mut value) = format.parse(&contents) else {
+            continue;
+        };
+        written.insert(asset_rel.clone());
+
+        let dest_path = dest_root.join(&asset_rel);
+        let data_path = expand_templates
+            .then(|| data_file_for(path, card_files))
+            .flatten();
+        let is_changed = |source: &Path| {
+            let rel = source.strip_prefix(cards_dir).unwrap_or(source);
+            changed.contains(&rel.to_string_lossy().replace('\\', "/"))
+        };
+        if dest_path.exists() && !is_changed(path) && !data_path.as_deref().is_some_and(is_changed)
+        {
+            continue;
+        }
         if let Some(parent) = dest_path.parent() {
             fs::create_dir_all(parent)?;
         }
-        fs::copy(path, &dest_path).with_context(|| format!("failed to copy {}", path.display()))?;
+        if format == CardFormat::Json && data_path.is_none() {
+            fs::copy(path, &dest_path)
+                .with_context(|| format!("failed to copy {}", path.display()))?;
+            continue;
+        }
+
+        if let Some(data_path) = data_path
+            && let Some(data_format) = CardFormat::from_path(&data_path)
+            && let Ok(data_contents) = fs::read_to_string(&data_path)
+            && let Ok(data) = data_format.parse(&data_contents)
+        {
+            value = expand_template(&value, &data);
+        }
+        fs::write(&dest_path, normalized_json(&value)?)
+            .with_context(|| format!("failed to write {}", dest_path.display()))?;
+    }
+
+    Ok(written)
+}
+
+/// Writes each card's input schema and returns the asset paths written.
+fn write_input_schemas(manifest: &Manifest, assets_cards: &Path) -> Result<Vec<PathBuf>> {
+    let mut written = Vec::new();
+    for card in manifest.flows.iter().flat_map(|flow| &flow.cards) {
+        if let (Some(schema), Some(rel)) = (&card.input_schema, &card.schema_asset_path) {
+            let path = assets_cards.join(rel);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, normalized_json(schema)?)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            written.push(PathBuf::from(rel));
+        }
+    }
+    Ok(written)
+}
+
+/// Flow files the previous run emitted, from its manifest. When that manifest cannot be read, a
+/// `pack_output` warning is recorded and the `flows/*.ygtc` entries of `pack.yaml` are used instead.
+fn previous_flow_files(out: &Path, warnings: &mut Vec<Warning>) -> Result<BTreeSet<PathBuf>> {
+    let manifest_path = out.join(".cards2pack").join("manifest.json");
+    if !manifest_path.exists() {
+        return Ok(BTreeSet::new());
+    }
+    match read_manifest(&manifest_path) {
+        Ok(previous) => Ok(previous
+            .flows
+            .iter()
+            .map(|flow| out.join("flows").join(format!("{}.ygtc", flow.flow_name)))
+            .collect()),
+        Err(err) => {
+            warnings.push(
+                warning(
+                    WarningKind::PackOutput,
+                    format!(
+                        "previous manifest is unreadable ({err:#}); treating the flows listed in pack.yaml as generated"
+                    ),
+                )
+                .at(Location::file(".cards2pack/manifest.json")),
+            );
+            pack_yaml_flow_files(&out.join("pack.yaml"))
+        }
+    }
+}
+
+/// `flows/*.ygtc` files registered in `pack.yaml`, if it exists.
+fn pack_yaml_flow_files(pack_yaml: &Path) -> Result<BTreeSet<PathBuf>> {
+    if !pack_yaml.exists() {
+        return Ok(BTreeSet::new());
+    }
+    let contents =
+        fs::read_to_string(pack_yaml).with_context(|| format!("read {}", pack_yaml.display()))?;
+    let manifest: YamlValue =
+        serde_yaml_bw::from_str(&contents).context("parse pack manifest yaml for flows")?;
+    let root = pack_yaml.parent().unwrap_or_else(|| Path::new("."));
+    Ok(manifest
+        .get("flows")
+        .and_then(YamlValue::as_sequence)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.get("file").and_then(YamlValue::as_str))
+        .map(Path::new)
+        .filter(|file| {
+            file.parent() == Some(Path::new("flows"))
+                && file
+                    .extension()
+                    .is_some_and(|extension| extension == "ygtc")
+        })
+        .map(|file| root.join(file))
+        .collect())
+}
+
+fn flow_files(flows_dir: &Path) -> BTreeSet<PathBuf> {
+    let Ok(entries) = fs::read_dir(flows_dir) else {
+        return BTreeSet::new();
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "ygtc")
+        })
+        .collect()
+}
+
+/// Deletes flows that are no longer generated, with their resolve sidecars.
+fn remove_stale_flows<'a>(flows: impl Iterator<Item = &'a PathBuf>) -> Result<()> {
+    for flow in flows {
+        for path in [
+            flow.clone(),
+            flow.with_extension("ygtc.resolve.json"),
+            flow.with_extension("ygtc.resolve.summary.json"),
+        ] {
+            if path.exists() {
+                fs::remove_file(&path)
+                    .with_context(|| format!("failed to remove stale flow {}", path.display()))?;
+            }
+        }
     }
+    Ok(())
+}
 
+/// Deletes files under `assets/cards` left by earlier runs whose source is gone or no longer
+/// passes the card filter, so they are not packaged, then prunes directories left empty.
+fn remove_stale_assets(assets_cards: &Path, keep: &BTreeSet<PathBuf>) -> Result<()> {
+    let mut dirs = Vec::new();
+    for entry in WalkDir::new(assets_cards).min_depth(1) {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type().is_dir() {
+            dirs.push(path.to_path_buf());
+            continue;
+        }
+        let rel = path.strip_prefix(assets_cards).unwrap_or(path);
+        if !keep.contains(rel) {
+            fs::remove_file(path)
+                .with_context(|| format!("failed to remove stale asset {}", path.display()))?;
+        }
+    }
+    // Deepest first, so a parent is only checked after its children are pruned.
+    for dir in dirs.iter().rev() {
+        if fs::read_dir(dir)?.next().is_none() {
+            fs::remove_dir(dir).with_context(|| format!("failed to remove {}", dir.display()))?;
+        }
+    }
     Ok(())
 }
 
@@ -271,6 +673,123 @@ fn default_flow_file(pack_yaml: &Path) -> Result<Option<PathBuf>> {
     Ok(None)
 }
 
+fn select_default_flow(
+    flow_names: &[String],
+    requested: Option<&str>,
+    current_default: Option<&Path>,
+    policy: SeverityPolicy<'_>,
+    warnings: &mut Vec<Warning>,
+    errors: &mut StrictErrors,
+) -> Result<Option<String>> {
+    if let Some(requested) = requested {
+        if flow_names.iter().any(|name| name == requested) {
+            return Ok(Some(requested.to_string()));
+        }
+        let message = format!("default flow {requested} has no cards");
+        policy.report(
+            warning(WarningKind::MissingFlow, message).flow(requested),
+            warnings,
+            errors,
+        );
+    }
+
+    if let Some(current) = current_default
+        .and_then(|path| path.file_stem())
+        .and_then(|stem| stem.to_str())
+        && flow_names.iter().any(|name| name == current)
+    {
+        return Ok(Some(current.to_string()));
+    }
+
+    Ok(flow_names.first().cloned())
+}
+
+fn register_flows_in_pack_yaml(
+    pack_yaml: &Path,
+    flows: &[(String, String)],
+    default_flow: Option<&str>,
+) -> Result<()> {
+    let contents =
+        fs::read_to_string(pack_yaml).with_context(|| format!("read {}", pack_yaml.display()))?;
+    let mut manifest: YamlValue =
+        serde_yaml_bw::from_str(&contents).context("parse pack manifest yaml for flows")?;
+    let root = pack_yaml.parent().unwrap_or_else(|| Path::new("."));
+    let mapping = manifest
+        .as_mapping_mut()
+        .ok_or_else(|| anyhow!("pack manifest {} is not a mapping", pack_yaml.display()))?;
+
+    let mut entries: Vec<YamlValue> = mapping
+        .get("flows")
+        .and_then(YamlValue::as_sequence)
+        .map(|sequence| sequence.to_vec())
+        .unwrap_or_default();
+    entries.retain(|entry| {
+        entry
+            .get("file")
+            .and_then(YamlValue::as_str)
+            .is_none_or(|file| root.join(file).exists())
+    });
+    for (_, file) in flows {
+        let known = entries
+            .iter()
+            .any(|entry| entry.get("file").and_then(YamlValue::as_str) == Some(file.as_str()));
+        if !known {
+            let mut entry = serde_yaml_bw::Mapping::new();
+            entry.insert("file".into(), file.as_str().into());
+            entries.push(YamlValue::Mapping(entry));
+        }
+    }
+
+    let default_file = default_flow.and_then(|default| {
+        flows
+            .iter()
+            .find(|(name, _)| name == default)
+            .map(|(_, file)| file.as_str())
+    });
+    for entry in &mut entries {
+        let file = entry
+            .get("file")
+            .and_then(YamlValue::as_str)
+            .map(|file| file.to_string());
+        let generated = flows
+            .iter()
+            .find(|(_, candidate)| Some(candidate) == file.as_ref())
+            .map(|(name, _)| name.clone());
+        if generated.is_none() && default_file.is_none() {
+            continue;
+        }
+
+        let mut entrypoints: Vec<String> = entry
+            .get("entrypoints")
+            .and_then(YamlValue::as_sequence)
+            .map(|sequence| {
+                sequence
+                    .iter()
+                    .filter_map(YamlValue::as_str)
+                    .filter(|value| *value != "default")
+                    .map(|value| value.to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        if let Some(name) = generated
+            && !entrypoints.contains(&name)
+        {
+            entrypoints.push(name);
+        }
+        if default_file.is_some() && file.as_deref() == default_file {
+            entrypoints.insert(0, "default".to_string());
+        }
+        if let Some(entry) = entry.as_mapping_mut() {
+            entry.insert("entrypoints".into(), entrypoints.into());
+        }
+    }
+
+    mapping.insert("flows".into(), entries.into());
+    let serialized = serde_yaml_bw::to_string(&manifest).context("serialize pack manifest yaml")?;
+    fs::write(pack_yaml, serialized).with_context(|| format!("write {}", pack_yaml.display()))?;
+    Ok(())
+}
+
 fn insert_prompt_node(flow_path: &Path) -> Result<()> {
     let contents =
         fs::read_to_string(flow_path).with_context(|| format!("read {}", flow_path.display()))?;
@@ -383,7 +902,7 @@ fn sync_local_component_if_configured(
     pack_root: &Path,
     greentic_pack_bin: &Path,
     manifest: &mut Manifest,
-    strict: bool,
+    policy: SeverityPolicy<'_>,
 ) -> Result<()> {
     let manifest_path = match env::var(COMPONENT_MANIFEST_ENV) {
         Ok(value) if !value.trim().is_empty() => Some(PathBuf::from(value)),
@@ -405,13 +924,7 @@ fn sync_local_component_if_configured(
                 "{} is set but {} is not",
                 COMPONENT_WASM_ENV, COMPONENT_MANIFEST_ENV
             );
-            if strict {
-                return Err(anyhow!(message));
-            }
-            manifest
-                .warnings
-                .push(warning(WarningKind::PackOutput, message));
-            return Ok(());
+            return report_component_env(policy, manifest, message);
         }
     };
     let wasm_path = match wasm_path {
@@ -421,13 +934,7 @@ fn sync_local_component_if_configured(
                 "{} is set but {} is not",
                 COMPONENT_MANIFEST_ENV, COMPONENT_WASM_ENV
             );
-            if strict {
-                return Err(anyhow!(message));
-            }
-            manifest
-                .warnings
-                .push(warning(WarningKind::PackOutput, message));
-            return Ok(());
+            return report_component_env(policy, manifest, message);
         }
     };
 
@@ -478,6 +985,21 @@ fn sync_local_component_if_configured(
     Ok(())
 }
 
+/// Records a half-configured local component; when `pack_output` is denied the run stops here.
+fn report_component_env(
+    policy: SeverityPolicy<'_>,
+    manifest: &mut Manifest,
+    message: String,
+) -> Result<()> {
+    let mut denied = StrictErrors::default();
+    policy.report(
+        warning(WarningKind::PackOutput, message).tool("greentic-pack components"),
+        &mut manifest.warnings,
+        &mut denied,
+    );
+    denied.finish_with(&manifest.warnings)
+}
+
 fn pack_yaml_version(pack_root: &Path) -> Option<String> {
     let contents = fs::read_to_string(pack_root.join("pack.yaml")).ok()?;
     let yaml: serde_yaml_bw::Value = serde_yaml_bw::from_str(&contents).ok()?;
@@ -514,6 +1036,36 @@ fn write_manifest(state_dir: &Path, manifest: &Manifest) -> Result<()> {
     Ok(())
 }
 
+/// Writes the SARIF log for a run: its warnings when it succeeded, or every finding (the denied
+/// ones as errors) when strict mode or `--deny` failed it. Other failures write nothing.
+pub fn write_sarif_for(path: &Path, outcome: Result<&Manifest, &anyhow::Error>) -> Result<()> {
+    let findings = match outcome {
+        Ok(manifest) => &manifest.warnings,
+        Err(err) => match err.downcast_ref::<StrictFailure>() {
+            Some(failure) => &failure.findings,
+            None => return Ok(()),
+        },
+    };
+    write_sarif(path, findings)
+}
+
+/// Writes `warnings` as a SARIF 2.1.0 log for code-scanning tools.
+pub fn write_sarif(path: &Path, warnings: &[Warning]) -> Result<()> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let json = serde_json::to_vec_pretty(&sarif_log(warnings))?;
+    let mut file =
+        fs::File::create(path).with_context(|| format!("failed to write {}", path.display()))?;
+    file.write_all(&json)?;
+    file.write_all(b"\n")?;
+
+    Ok(())
+}
+
 fn update_readme(workspace: &Path, name: &str, entries: &[(String, String)]) -> Result<()> {
     let readme_path = workspace.join("README.md");
     let existing = if readme_path.exists() {
@@ -556,6 +1108,7 @@ fn run_greentic_flow_doctor(flows_dir: &Path) -> Result<()> {
     let status = std::process::Command::new("greentic-flow")
         .arg("doctor")
         .arg(flows_dir)
+        .stdout(Stdio::from(io::stderr()))
         .status()
         .with_context(|| {
             format!(
@@ -609,8 +1162,14 @@ fn extract_gtpack_path(build_output: &crate::tools::BuildOutput) -> Option<PathB
     None
 }
 
-fn write_flow_resolve_sidecar(flow_path: &Path, graph: &crate::graph::FlowGraph) -> Result<()> {
-    let component_source = if let Some(local_wasm) = component_wasm_path(flow_path) {
+/// `final_flows_dir` is where the flow file will finally live; a local component path is written
+/// relative to it.
+fn write_flow_resolve_sidecar(
+    flow_path: &Path,
+    final_flows_dir: &Path,
+    graph: &crate::graph::FlowGraph,
+) -> Result<()> {
+    let component_source = if let Some(local_wasm) = component_wasm_path(final_flows_dir) {
         serde_json::json!({
             "kind": "local",
             "path": format!("file://{local_wasm}")
@@ -650,13 +1209,12 @@ fn write_flow_resolve_sidecar(flow_path: &Path, graph: &crate::graph::FlowGraph)
     Ok(())
 }
 
-fn component_wasm_path(flow_path: &Path) -> Option<String> {
+fn component_wasm_path(flow_dir: &Path) -> Option<String> {
     let value = env::var(COMPONENT_WASM_ENV).ok()?;
     let trimmed = value.trim();
     if trimmed.is_empty() {
         return None;
     }
-    let flow_dir = flow_path.parent()?;
     let abs = if Path::new(trimmed).is_absolute() {
         PathBuf::from(trimmed)
     } else {
@@ -731,7 +1289,8 @@ fn ensure_named_gtpack(dist_dir: &Path, name: &str) -> Result<(PathBuf, Option<W
             source.display(),
             target_path.display()
         ),
-    );
+    )
+    .tool("greentic-pack build");
 
     if source != target_path && fs::rename(&source, &target_path).is_err() {
         fs::copy(&source, &target_path)?;
@@ -797,6 +1356,53 @@ nodes:
         assert!(updated.find("prompt2flow:").unwrap() < updated.find("start:").unwrap());
     }
 
+    #[test]
+    fn register_flows_moves_default_and_drops_missing_files() {
+        let tmp = TempDir::new().expect("temp dir");
+        fs::create_dir_all(tmp.path().join("flows")).expect("flows dir");
+        fs::write(tmp.path().join("flows/hr.ygtc"), "").expect("write hr flow");
+        fs::write(tmp.path().join("flows/sales.ygtc"), "").expect("write sales flow");
+        let pack_yaml = tmp.path().join("pack.yaml");
+        fs::write(
+            &pack_yaml,
+            "name: demo\nflows:\n  - file: flows/main.ygtc\n    entrypoints:\n      - default\n  - file: flows/hr.ygtc\n    entrypoints:\n      - default\n      - custom\n",
+        )
+        .expect("write pack.yaml");
+
+        let flows = vec![
+            ("hr".to_string(), "flows/hr.ygtc".to_string()),
+            ("sales".to_string(), "flows/sales.ygtc".to_string()),
+        ];
+        register_flows_in_pack_yaml(&pack_yaml, &flows, Some("sales")).expect("register flows");
+
+        let updated: YamlValue =
+            serde_yaml_bw::from_str(&fs::read_to_string(&pack_yaml).expect("read pack.yaml"))
+                .expect("parse pack.yaml");
+        let entries = updated
+            .get("flows")
+            .and_then(YamlValue::as_sequence)
+            .expect("flows");
+        let files: Vec<&str> = entries
+            .iter()
+            .filter_map(|entry| entry.get("file").and_then(YamlValue::as_str))
+            .collect();
+        assert_eq!(files, vec!["flows/hr.ygtc", "flows/sales.ygtc"]);
+        let entrypoints: Vec<Vec<&str>> = entries
+            .iter()
+            .map(|entry| {
+                entry
+                    .get("entrypoints")
+                    .and_then(YamlValue::as_sequence)
+                    .expect("entrypoints")
+                    .iter()
+                    .filter_map(YamlValue::as_str)
+                    .collect()
+            })
+            .collect();
+        assert_eq!(entrypoints[0], vec!["custom", "hr"]);
+        assert_eq!(entrypoints[1], vec!["default", "sales"]);
+    }
+
     #[test]
     fn prompt_node_error_when_not_first() {
         let flow_contents = "\
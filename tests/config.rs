@@ -0,0 +1,283 @@
+mod support;
+
+use std::fs;
+
+use assert_cmd::cargo::cargo_bin_cmd;
+use clap::Parser;
+use greentic_cards2pack::cli::{Cli, Commands, GroupBy};
+use greentic_cards2pack::config::resolve_generate;
+use greentic_cards2pack::ir::{Severity, WarningKind};
+use serde_json::Value;
+use tempfile::TempDir;
+
+fn generate_args(args: &[&str]) -> greentic_cards2pack::cli::GenerateArgs {
+    let mut argv = vec!["greentic-cards2pack", "generate"];
+    argv.extend_from_slice(args);
+    match Cli::try_parse_from(argv).unwrap().command {
+        Commands::Generate(args) => args,
+        _ => panic!("expected generate"),
+    }
+}
+
+#[test]
+fn discovers_yaml_next_to_cards_and_lets_flags_win() {
+    let tmp = TempDir::new().unwrap();
+    let cards = tmp.path().join("cards");
+    fs::create_dir_all(&cards).unwrap();
+    fs::write(
+        tmp.path().join("cards2pack.yaml"),
+        "out: ./packs/demo\nname: from-config\ngroup_by: folder\ndefault_flow: main\nstrict: true\n",
+    )
+    .unwrap();
+
+    let cards_arg = cards.to_string_lossy().to_string();
+    let options = resolve_generate(&generate_args(&[
+        "--cards",
+        &cards_arg,
+        "--name",
+        "from-flag",
+    ]))
+    .unwrap();
+
+    assert_eq!(options.name, "from-flag");
+    assert_eq!(options.out, tmp.path().join("./packs/demo"));
+    assert_eq!(options.group_by, Some(GroupBy::Folder));
+    assert_eq!(options.default_flow.as_deref(), Some("main"));
+    assert!(options.strict);
+    assert_eq!(
+        options.config_path.as_deref(),
+        Some(tmp.path().join("cards2pack.yaml").as_path())
+    );
+}
+
+#[test]
+fn no_flags_turn_off_config_booleans() {
+    let tmp = TempDir::new().unwrap();
+    let cards = tmp.path().join("cards");
+    fs::create_dir_all(&cards).unwrap();
+    fs::write(
+        tmp.path().join("cards2pack.yaml"),
+        "out: out\nname: demo\nstrict: true\nverbose: true\nprompt: true\nexpand_templates: true\nlocale_suffixes: true\n",
+    )
+    .unwrap();
+
+    let cards_arg = cards.to_string_lossy().to_string();
+    let options = resolve_generate(&generate_args(&[
+        "--cards",
+        &cards_arg,
+        "--no-strict",
+        "--no-verbose",
+        "--no-prompt",
+        "--no-expand-templates",
+        "--no-locale-suffixes",
+    ]))
+    .unwrap();
+    assert!(!options.strict);
+    assert!(!options.verbose);
+    assert!(!options.prompt);
+    assert!(!options.expand_templates);
+    assert!(!options.locale_suffixes);
+
+    let options = resolve_generate(&generate_args(&[
+        "--cards",
+        &cards_arg,
+        "--no-strict",
+        "--strict",
+    ]))
+    .unwrap();
+    assert!(options.strict);
+    assert!(options.verbose);
+}
+
+#[test]
+fn reads_explicit_toml_config() {
+    let tmp = TempDir::new().unwrap();
+    let config = tmp.path().join("cards2pack.toml");
+    fs::write(
+        &config,
+        "cards = \"cards\"\nout = 'out'\nname = \"toml-\\u0064emo\"\nprompt = true\ndefault_flow = \"\"\"\nmain\"\"\"\nprompt_limits.max_intents = 5\nprompt_limits.max_examples_per_intent = 4\nprompt_limits.max_keywords = 3\nprompt_limits.max_anchors = 2\n\n[routing_keys]\ntarget = [\"/data/next\"]\n",
+    )
+    .unwrap();
+
+    let config_arg = config.to_string_lossy().to_string();
+    let options = resolve_generate(&generate_args(&["--config", &config_arg])).unwrap();
+
+    assert_eq!(options.cards, tmp.path().join("cards"));
+    assert_eq!(options.name, "toml-demo");
+    assert_eq!(options.default_flow.as_deref(), Some("main"));
+    assert!(options.prompt);
+    assert_eq!(options.prompt_limits.unwrap().max_intents, 5);
+    assert_eq!(options.routing_keys.target, vec!["/data/next"]);
+}
+
+#[test]
+fn rejects_unknown_config_keys() {
+    let tmp = TempDir::new().unwrap();
+    let config = tmp.path().join("cards2pack.yaml");
+    fs::write(&config, "cards: cards\nnmae: typo\n").unwrap();
+
+    let config_arg = config.to_string_lossy().to_string();
+    assert!(resolve_generate(&generate_args(&["--config", &config_arg])).is_err());
+}
+
+// Runs `generate` against a stub `greentic-flow`, which is only written for Unix shells.
+#[cfg(unix)]
+#[test]
+fn generate_records_resolved_config_in_manifest() {
+    let tmp = TempDir::new().unwrap();
+    let cards = tmp.path().join("cards");
+    fs::create_dir_all(&cards).unwrap();
+    fs::write(
+        cards.join("card.json"),
+        "{ \"type\": \"AdaptiveCard\", \"actions\": [] }\n",
+    )
+    .unwrap();
+    fs::write(
+        tmp.path().join("cards2pack.yaml"),
+        "cards: cards\nout: workspace\nname: demo\ndefault_flow: ops\n",
+    )
+    .unwrap();
+
+    let bin_dir = tmp.path().join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let greentic_pack = support::create_fake_greentic_pack(&bin_dir);
+    let path = support::install_stub_greentic_flow(&bin_dir);
+
+    cargo_bin_cmd!("greentic-cards2pack")
+        .env("PATH", path)
+        .arg("generate")
+        .arg("--config")
+        .arg(tmp.path().join("cards2pack.yaml"))
+        .arg("--greentic-pack-bin")
+        .arg(greentic_pack)
+        .assert()
+        .success();
+
+    let out = tmp.path().join("workspace");
+    assert!(out.join("flows/ops.ygtc").is_file());
+    let manifest: Value =
+        serde_json::from_str(&fs::read_to_string(out.join(".cards2pack/manifest.json")).unwrap())
+            .unwrap();
+    assert_eq!(manifest["config"]["name"], "demo");
+    assert_eq!(manifest["config"]["default_flow"], "ops");
+    assert!(
+        manifest["config"]["config_path"]
+            .as_str()
+            .unwrap()
+            .ends_with("cards2pack.yaml")
+    );
+}
+
+#[test]
+fn merges_routing_keys_from_config_and_flags() {
+    let tmp = TempDir::new().unwrap();
+    let cards = tmp.path().join("cards");
+    fs::create_dir_all(&cards).unwrap();
+    fs::write(
+        tmp.path().join("cards2pack.yaml"),
+        "out: out\nname: demo\nrouting_keys:\n  target: /data/next\n  action_flow: [/data/journey, /data/flow]\n",
+    )
+    .unwrap();
+
+    let cards_arg = cards.to_string_lossy().to_string();
+    let options = resolve_generate(&generate_args(&[
+        "--cards",
+        &cards_arg,
+        "--target-key",
+        "/data/action/target",
+        "--target-key",
+        "/data/next",
+    ]))
+    .unwrap();
+
+    let keys = &options.routing_keys;
+    assert_eq!(keys.target, vec!["/data/action/target", "/data/next"]);
+    assert_eq!(keys.action_flow, vec!["/data/journey", "/data/flow"]);
+    assert_eq!(keys.card_id, vec!["/greentic/cardId"]);
+}
+
+#[test]
+fn merges_severity_overrides_from_config_and_flags() {
+    let tmp = TempDir::new().unwrap();
+    let cards = tmp.path().join("cards");
+    fs::create_dir_all(&cards).unwrap();
+    fs::write(
+        tmp.path().join("cards2pack.yaml"),
+        "out: out\nname: demo\ndeny: [missing_target, ignored_file]\nallow: [no_routes]\n",
+    )
+    .unwrap();
+
+    let cards_arg = cards.to_string_lossy().to_string();
+    let options = resolve_generate(&generate_args(&[
+        "--cards",
+        &cards_arg,
+        "--allow",
+        "ignored_file",
+        "--deny",
+        "C2P012",
+    ]))
+    .unwrap();
+
+    let severities: Vec<_> = options.severities.into_iter().collect();
+    assert_eq!(
+        severities,
+        vec![
+            (WarningKind::MissingTarget, Severity::Deny),
+            (WarningKind::IgnoredFile, Severity::Allow),
+            (WarningKind::MissingLocale, Severity::Deny),
+            (WarningKind::NoRoutes, Severity::Allow),
+        ]
+    );
+}
+
+#[test]
+fn reads_severity_overrides_by_code_from_config() {
+    let tmp = TempDir::new().unwrap();
+    let cards = tmp.path().join("cards");
+    fs::create_dir_all(&cards).unwrap();
+    fs::write(
+        tmp.path().join("cards2pack.yaml"),
+        "out: out\nname: demo\ndeny: [C2P002]\nallow: [C2P013, ignored_file]\n",
+    )
+    .unwrap();
+
+    let cards_arg = cards.to_string_lossy().to_string();
+    let options = resolve_generate(&generate_args(&["--cards", &cards_arg])).unwrap();
+
+    let severities: Vec<_> = options.severities.into_iter().collect();
+    assert_eq!(
+        severities,
+        vec![
+            (WarningKind::MissingTarget, Severity::Deny),
+            (WarningKind::IgnoredFile, Severity::Allow),
+            (WarningKind::NoRoutes, Severity::Allow),
+        ]
+    );
+}
+
+#[test]
+fn rejects_config_prompt_settings_without_prompt() {
+    let tmp = TempDir::new().unwrap();
+    let cards = tmp.path().join("cards");
+    fs::create_dir_all(&cards).unwrap();
+    let cards_arg = cards.to_string_lossy().to_string();
+
+    for (settings, key) in [
+        ("prompt_json: prompt.json\n", "`prompt_json`"),
+        (
+            "prompt: false\nprompt_limits: { max_intents: 5, max_examples_per_intent: 4, max_keywords: 3, max_anchors: 2 }\n",
+            "`prompt_limits`",
+        ),
+    ] {
+        fs::write(
+            tmp.path().join("cards2pack.yaml"),
+            format!("out: out\nname: demo\n{settings}"),
+        )
+        .unwrap();
+        let err = resolve_generate(&generate_args(&["--cards", &cards_arg])).unwrap_err();
+        assert!(err.to_string().contains(key), "{err}");
+
+        let options = resolve_generate(&generate_args(&["--cards", &cards_arg, "--prompt"]));
+        assert!(options.unwrap().prompt);
+    }
+}
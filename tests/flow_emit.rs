@@ -3,6 +3,7 @@ use std::path::{Path, PathBuf};
 
 use greentic_cards2pack::emit_flow::emit_flow;
 use greentic_cards2pack::graph::build_flow_graph;
+use greentic_cards2pack::ir::{RouteTarget, WarningKind};
 use greentic_cards2pack::scan::{ScanConfig, scan_cards};
 use tempfile::TempDir;
 
@@ -23,9 +24,8 @@ fn copy_fixture(rel: &str, dest_root: &Path) -> PathBuf {
 fn scan_flow(dir: &Path) -> greentic_cards2pack::ir::FlowGroup {
     let config = ScanConfig {
         cards_dir: dir.to_path_buf(),
-        group_by: None,
-        default_flow: None,
         strict: true,
+        ..ScanConfig::default()
     };
     let manifest = scan_cards(&config).unwrap();
     manifest.flows.into_iter().next().unwrap()
@@ -114,3 +114,117 @@ fn strict_mode_errors_on_missing_target() {
 
     assert!(result.is_err());
 }
+
+#[test]
+fn routes_execute_verbs() {
+    let cards_dir =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/cards/execute_verbs");
+    let flow = scan_flow(&cards_dir);
+    let request = flow
+        .cards
+        .iter()
+        .find(|card| card.card_id == "request")
+        .unwrap();
+    assert!(matches!(
+        request.actions[0].target,
+        Some(RouteTarget::Verb(ref verb)) if verb == "approve"
+    ));
+
+    let graph = build_flow_graph(&flow, true).unwrap();
+    let edges: Vec<(&str, &str, Option<&str>)> = graph.nodes["request"]
+        .routes
+        .iter()
+        .map(|route| {
+            (
+                route.key.as_str(),
+                route.target.as_str(),
+                route.verb.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        edges,
+        vec![
+            ("approve", "approve", Some("approve")),
+            ("reject", "rejected", Some("reject")),
+        ]
+    );
+
+    let tmp = TempDir::new().unwrap();
+    let flow_path = emit_flow(&graph, tmp.path(), true).unwrap().0;
+    let generated = extract_generated_block(&fs::read_to_string(flow_path).unwrap());
+    assert!(generated.contains("verb_routes:"));
+    assert!(generated.contains("reject: rejected"));
+    assert!(generated.contains("interaction_type: Execute"));
+}
+
+#[test]
+fn reports_verbs_routed_to_different_targets() {
+    let tmp = TempDir::new().unwrap();
+    let card = |id: &str, actions: &str| {
+        format!(
+            r#"{{"type":"AdaptiveCard","greentic":{{"flow":"main","cardId":"{id}"}},"actions":[{actions}]}}"#
+        )
+    };
+    fs::write(
+        tmp.path().join("request.json"),
+        card(
+            "request",
+            r#"{"type":"Action.Execute","verb":"go","data":{"step":"a"}},{"type":"Action.Execute","verb":"go","data":{"step":"b"}}"#,
+        ),
+    )
+    .unwrap();
+    fs::write(tmp.path().join("a.json"), card("a", "")).unwrap();
+    fs::write(tmp.path().join("b.json"), card("b", "")).unwrap();
+
+    let flow = scan_flow(tmp.path());
+    let graph = build_flow_graph(&flow, false).unwrap();
+    let verbs: Vec<(&str, Option<&str>)> = graph.nodes["request"]
+        .routes
+        .iter()
+        .map(|route| (route.target.as_str(), route.verb.as_deref()))
+        .collect();
+    assert_eq!(verbs, vec![("a", Some("go")), ("b", None)]);
+    let conflict = graph
+        .warnings
+        .iter()
+        .find(|warning| warning.message.starts_with("verb "))
+        .unwrap();
+    assert_eq!(conflict.kind, WarningKind::Inconsistent);
+    assert_eq!(
+        conflict.message,
+        "verb go in card request routes to both a and b; keeping a"
+    );
+
+    let err = build_flow_graph(&flow, true).unwrap_err().to_string();
+    assert!(
+        err.contains("[inconsistent] verb go in card request"),
+        "{err}"
+    );
+}
+
+#[test]
+fn card_payload_lists_localized_variants() {
+    let cards_dir =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/cards/localized");
+    let config = ScanConfig {
+        cards_dir,
+        locale_suffixes: true,
+        ..ScanConfig::default()
+    };
+    let manifest = scan_cards(&config).unwrap();
+    let graph = build_flow_graph(&manifest.flows[0], false).unwrap();
+
+    let tmp = TempDir::new().unwrap();
+    let flow_path = emit_flow(&graph, tmp.path(), false).unwrap().0;
+    let generated = extract_generated_block(&fs::read_to_string(flow_path).unwrap());
+    assert!(generated.contains("localized_asset_paths"), "{generated}");
+    assert!(
+        generated.contains("assets/cards/welcome.fr.json"),
+        "{generated}"
+    );
+    assert!(
+        generated.contains("assets/cards/done.en.json"),
+        "{generated}"
+    );
+}
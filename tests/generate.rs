@@ -38,8 +38,10 @@ if "%CMD%"=="new" (
   goto loopnew
   :donew
   if "%OUT%"=="" exit /b 1
-  if not exist "%OUT%" mkdir "%OUT%"
+  if not exist "%OUT%\flows" mkdir "%OUT%\flows"
   echo name: demo> "%OUT%\pack.yaml"
+  echo id: main> "%OUT%\flows\main.ygtc"
+  echo created pack in %OUT%
   exit /b 0
 )
 
@@ -117,8 +119,10 @@ case "$cmd" in
       esac
     done
     [[ -n "$out" ]] || { echo "missing --dir" >&2; exit 1; }
-    mkdir -p "$out"
+    mkdir -p "$out/flows"
     printf "name: demo\n" > "$out/pack.yaml"
+    printf "id: main\n" > "$out/flows/main.ygtc"
+    echo "created pack in $out"
     ;;
   update)
     out=""
@@ -204,12 +208,98 @@ fn generate_creates_workspace_and_dist() {
 
     assert!(out_dir.join("pack.yaml").is_file());
     assert!(out_dir.join("README.md").is_file());
-    assert!(out_dir.join("flows/main.ygtc").is_file());
+    assert!(out_dir.join("flows/misc.ygtc").is_file());
     assert!(out_dir.join("assets/cards/card.json").is_file());
     assert!(out_dir.join("dist/demo.gtpack").is_file());
     assert!(out_dir.join(".cards2pack/manifest.json").is_file());
 }
 
+#[test]
+fn generate_denies_emission_warnings_before_building_the_pack() {
+    let tmp = TempDir::new().unwrap();
+    let cards_dir = tmp.path().join("cards");
+    let out_dir = tmp.path().join("workspace");
+    fs::create_dir_all(&cards_dir).unwrap();
+    write_card(&cards_dir, "card.json");
+
+    let bin_dir = tmp.path().join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let greentic_pack = create_fake_greentic_pack(&bin_dir);
+
+    let output = cargo_bin_cmd!("greentic-cards2pack")
+        .arg("generate")
+        .arg("--cards")
+        .arg(&cards_dir)
+        .arg("--out")
+        .arg(&out_dir)
+        .arg("--name")
+        .arg("demo")
+        .arg("--deny")
+        .arg("no_routes")
+        .arg("--greentic-pack-bin")
+        .arg(greentic_pack)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("[no_routes] no routes for card"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("\nflows/misc.ygtc:\n"), "{stderr}");
+    assert!(!out_dir.join("dist/demo.gtpack").exists());
+}
+
+#[test]
+fn generate_writes_denied_component_env_findings_to_sarif() {
+    let tmp = TempDir::new().unwrap();
+    let cards_dir = tmp.path().join("cards");
+    let out_dir = tmp.path().join("workspace");
+    fs::create_dir_all(&cards_dir).unwrap();
+    write_card(&cards_dir, "card.json");
+    let sarif = tmp.path().join("cards2pack.sarif");
+
+    let bin_dir = tmp.path().join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let greentic_pack = create_fake_greentic_pack(&bin_dir);
+
+    let output = cargo_bin_cmd!("greentic-cards2pack")
+        .env("GREENTIC_COMPONENT_ADAPTIVE_CARD_WASM", "component.wasm")
+        .env_remove("GREENTIC_COMPONENT_ADAPTIVE_CARD_MANIFEST")
+        .arg("generate")
+        .arg("--cards")
+        .arg(&cards_dir)
+        .arg("--out")
+        .arg(&out_dir)
+        .arg("--name")
+        .arg("demo")
+        .arg("--deny")
+        .arg("pack_output")
+        .arg("--greentic-pack-bin")
+        .arg(greentic_pack)
+        .arg("--sarif")
+        .arg(&sarif)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("1 finding denied in 1 file:\ngreentic-pack components:"),
+        "{stderr}"
+    );
+    let log: Value = serde_json::from_str(&fs::read_to_string(&sarif).unwrap()).unwrap();
+    let denied: Vec<&Value> = log["runs"][0]["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|result| result["level"] == "error")
+        .collect();
+    assert_eq!(denied.len(), 1);
+    assert_eq!(denied[0]["ruleId"], "C2P008");
+}
+
 #[test]
 fn generate_copies_cards_preserving_layout() {
     let tmp = TempDir::new().unwrap();
@@ -244,6 +334,198 @@ fn generate_copies_cards_preserving_layout() {
     assert!(out_dir.join("assets/cards/sales/card.json").is_file());
 }
 
+#[test]
+fn generate_removes_assets_of_cards_no_longer_selected() {
+    let tmp = TempDir::new().unwrap();
+    let cards_dir = tmp.path().join("cards");
+    let out_dir = tmp.path().join("workspace");
+    fs::create_dir_all(&cards_dir).unwrap();
+    write_card(&cards_dir, "hr/welcome.json");
+    write_card(&cards_dir, "drafts/new.json");
+
+    let bin_dir = tmp.path().join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let greentic_pack = create_fake_greentic_pack(&bin_dir);
+    let generate = |extra: &[&str]| {
+        cargo_bin_cmd!("greentic-cards2pack")
+            .arg("generate")
+            .arg("--cards")
+            .arg(&cards_dir)
+            .arg("--out")
+            .arg(&out_dir)
+            .arg("--name")
+            .arg("demo")
+            .arg("--greentic-pack-bin")
+            .arg(&greentic_pack)
+            .args(extra)
+            .assert()
+            .success();
+    };
+
+    generate(&[]);
+    assert!(out_dir.join("assets/cards/drafts/new.json").is_file());
+
+    generate(&["--exclude", "drafts/**"]);
+    assert!(out_dir.join("assets/cards/hr/welcome.json").is_file());
+    assert!(!out_dir.join("assets/cards/drafts").exists());
+
+    write_card(&cards_dir, "drafts/new.json");
+    fs::write(cards_dir.join(".cards2packignore"), "hr/\n").unwrap();
+    generate(&[]);
+    assert!(out_dir.join("assets/cards/drafts/new.json").is_file());
+    assert!(!out_dir.join("assets/cards/hr/welcome.json").exists());
+}
+
+#[test]
+fn generate_removes_assets_of_cards_that_no_longer_parse() {
+    let tmp = TempDir::new().unwrap();
+    let cards_dir = tmp.path().join("cards");
+    let out_dir = tmp.path().join("workspace");
+    fs::create_dir_all(&cards_dir).unwrap();
+    write_card(&cards_dir, "hr/welcome.json");
+    write_card(&cards_dir, "hr/broken.json");
+
+    let bin_dir = tmp.path().join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let greentic_pack = create_fake_greentic_pack(&bin_dir);
+    let generate = |extra: &[&str]| {
+        cargo_bin_cmd!("greentic-cards2pack")
+            .arg("generate")
+            .arg("--cards")
+            .arg(&cards_dir)
+            .arg("--out")
+            .arg(&out_dir)
+            .arg("--name")
+            .arg("demo")
+            .arg("--greentic-pack-bin")
+            .arg(&greentic_pack)
+            .args(extra)
+            .output()
+            .unwrap()
+    };
+
+    assert!(generate(&[]).status.success());
+    assert!(out_dir.join("assets/cards/hr/broken.json").is_file());
+
+    fs::write(cards_dir.join("hr/broken.json"), "{ not json").unwrap();
+    assert!(!generate(&["--strict"]).status.success());
+
+    assert!(generate(&[]).status.success());
+    assert!(out_dir.join("assets/cards/hr/welcome.json").is_file());
+    assert!(!out_dir.join("assets/cards/hr/broken.json").exists());
+    let manifest = fs::read_to_string(out_dir.join(".cards2pack/manifest.json")).unwrap();
+    let manifest: Value = serde_json::from_str(&manifest).unwrap();
+    assert!(
+        manifest["warnings"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|warning| warning["kind"] == "invalid_json"
+                && warning["message"].as_str().unwrap().contains("broken.json")),
+        "{manifest:#}"
+    );
+}
+
+#[test]
+fn generate_removes_flows_no_longer_generated() {
+    let tmp = TempDir::new().unwrap();
+    let cards_dir = tmp.path().join("cards");
+    let out_dir = tmp.path().join("workspace");
+    fs::create_dir_all(&cards_dir).unwrap();
+    write_card(&cards_dir, "hr/welcome.json");
+    write_card(&cards_dir, "sales/quote.json");
+
+    let bin_dir = tmp.path().join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let greentic_pack = create_fake_greentic_pack(&bin_dir);
+    let generate = || {
+        cargo_bin_cmd!("greentic-cards2pack")
+            .arg("generate")
+            .arg("--cards")
+            .arg(&cards_dir)
+            .arg("--out")
+            .arg(&out_dir)
+            .arg("--name")
+            .arg("demo")
+            .arg("--group-by")
+            .arg("folder")
+            .arg("--greentic-pack-bin")
+            .arg(&greentic_pack)
+            .assert()
+            .success();
+    };
+
+    generate();
+    // The flow `greentic-pack new` scaffolded is not the default any more.
+    assert!(!out_dir.join("flows/main.ygtc").exists());
+    assert!(out_dir.join("flows/sales.ygtc").is_file());
+    assert!(out_dir.join("flows/sales.ygtc.resolve.json").is_file());
+
+    fs::remove_dir_all(cards_dir.join("sales")).unwrap();
+    fs::write(out_dir.join("flows/custom.ygtc"), "id: custom\n").unwrap();
+    generate();
+    assert!(out_dir.join("flows/hr.ygtc").is_file());
+    assert!(!out_dir.join("flows/sales.ygtc").exists());
+    assert!(!out_dir.join("flows/sales.ygtc.resolve.json").exists());
+    assert!(out_dir.join("flows/custom.ygtc").is_file());
+    let pack_yaml = fs::read_to_string(out_dir.join("pack.yaml")).unwrap();
+    assert!(!pack_yaml.contains("sales"), "{pack_yaml}");
+    assert!(pack_yaml.contains("flows/hr.ygtc"), "{pack_yaml}");
+}
+
+#[test]
+fn generate_removes_pack_yaml_flows_when_previous_manifest_is_unreadable() {
+    let tmp = TempDir::new().unwrap();
+    let cards_dir = tmp.path().join("cards");
+    let out_dir = tmp.path().join("workspace");
+    fs::create_dir_all(&cards_dir).unwrap();
+    write_card(&cards_dir, "hr/welcome.json");
+    write_card(&cards_dir, "sales/quote.json");
+
+    let bin_dir = tmp.path().join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let greentic_pack = create_fake_greentic_pack(&bin_dir);
+    let generate = || {
+        cargo_bin_cmd!("greentic-cards2pack")
+            .arg("generate")
+            .arg("--cards")
+            .arg(&cards_dir)
+            .arg("--out")
+            .arg(&out_dir)
+            .arg("--name")
+            .arg("demo")
+            .arg("--group-by")
+            .arg("folder")
+            .arg("--greentic-pack-bin")
+            .arg(&greentic_pack)
+            .arg("--format")
+            .arg("json")
+            .assert()
+            .success()
+    };
+
+    generate();
+    assert!(out_dir.join("flows/sales.ygtc").is_file());
+
+    fs::remove_dir_all(cards_dir.join("sales")).unwrap();
+    fs::write(out_dir.join(".cards2pack/manifest.json"), "{ not json").unwrap();
+    let output = generate();
+    assert!(out_dir.join("flows/hr.ygtc").is_file());
+    assert!(!out_dir.join("flows/sales.ygtc").exists());
+    let report: Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let warnings = report["warnings"].as_array().unwrap();
+    assert!(
+        warnings
+            .iter()
+            .any(|warning| warning["kind"] == "pack_output"
+                && warning["message"]
+                    .as_str()
+                    .unwrap()
+                    .starts_with("previous manifest is unreadable")),
+        "{warnings:?}"
+    );
+}
+
 #[test]
 fn generate_renames_or_selects_gtpack_to_name() {
     let tmp = TempDir::new().unwrap();
@@ -284,3 +566,525 @@ fn generate_renames_or_selects_gtpack_to_name() {
         warning.get("kind").and_then(|value| value.as_str()) == Some("pack_output")
     }));
 }
+
+#[test]
+fn generate_emits_one_flow_per_group() {
+    let tmp = TempDir::new().unwrap();
+    let cards_dir = tmp.path().join("cards");
+    let out_dir = tmp.path().join("workspace");
+    fs::create_dir_all(&cards_dir).unwrap();
+    write_card(&cards_dir, "hr/card.json");
+    write_card(&cards_dir, "sales/card.json");
+
+    let bin_dir = tmp.path().join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let greentic_pack = create_fake_greentic_pack(&bin_dir);
+
+    cargo_bin_cmd!("greentic-cards2pack")
+        .arg("generate")
+        .arg("--cards")
+        .arg(&cards_dir)
+        .arg("--out")
+        .arg(&out_dir)
+        .arg("--name")
+        .arg("demo")
+        .arg("--group-by")
+        .arg("folder")
+        .arg("--default-flow")
+        .arg("sales")
+        .arg("--greentic-pack-bin")
+        .arg(greentic_pack)
+        .assert()
+        .success();
+
+    assert!(out_dir.join("flows/hr.ygtc").is_file());
+    assert!(out_dir.join("flows/sales.ygtc").is_file());
+    assert!(out_dir.join("flows/hr.ygtc.resolve.json").is_file());
+    assert!(out_dir.join("flows/sales.ygtc.resolve.json").is_file());
+
+    let pack_yaml: serde_yaml_bw::Value =
+        serde_yaml_bw::from_str(&fs::read_to_string(out_dir.join("pack.yaml")).unwrap()).unwrap();
+    let flows = pack_yaml.get("flows").unwrap().as_sequence().unwrap();
+    let entrypoints = |file: &str| -> Vec<String> {
+        flows
+            .iter()
+            .find(|flow| flow.get("file").and_then(|value| value.as_str()) == Some(file))
+            .and_then(|flow| flow.get("entrypoints"))
+            .and_then(|value| value.as_sequence())
+            .unwrap()
+            .iter()
+            .filter_map(|value| value.as_str().map(str::to_string))
+            .collect()
+    };
+    assert_eq!(entrypoints("flows/hr.ygtc"), vec!["hr"]);
+    assert_eq!(entrypoints("flows/sales.ygtc"), vec!["default", "sales"]);
+}
+
+#[test]
+fn generate_normalises_yaml_and_jsonc_cards() {
+    let tmp = TempDir::new().unwrap();
+    let cards_dir = tmp.path().join("cards");
+    let out_dir = tmp.path().join("workspace");
+    fs::create_dir_all(cards_dir.join("hr")).unwrap();
+    fs::write(
+        cards_dir.join("hr/welcome.yaml"),
+        "type: AdaptiveCard\nbody:\n  - type: Input.Text\n    id: fullName\n    isRequired: true\nactions:\n  - type: Action.Submit\n    title: Next\n    data:\n      step: details\n",
+    )
+    .unwrap();
+    fs::write(cards_dir.join(".cards2packignore"), "*.draft.json\n").unwrap();
+    write_card(&cards_dir, "hr/welcome.draft.json");
+    fs::write(
+        cards_dir.join("hr/details.jsonc"),
+        "{\n  // authored with comments\n  \"type\": \"AdaptiveCard\",\n  \"body\": [],\n}\n",
+    )
+    .unwrap();
+
+    let bin_dir = tmp.path().join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let greentic_pack = create_fake_greentic_pack(&bin_dir);
+
+    cargo_bin_cmd!("greentic-cards2pack")
+        .arg("generate")
+        .arg("--cards")
+        .arg(&cards_dir)
+        .arg("--out")
+        .arg(&out_dir)
+        .arg("--name")
+        .arg("demo")
+        .arg("--group-by")
+        .arg("folder")
+        .arg("--greentic-pack-bin")
+        .arg(greentic_pack)
+        .assert()
+        .success();
+
+    let welcome: Value = serde_json::from_str(
+        &fs::read_to_string(out_dir.join("assets/cards/hr/welcome.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(welcome["actions"][0]["data"]["step"], "details");
+    assert!(out_dir.join("assets/cards/hr/details.json").is_file());
+    assert!(!out_dir.join("assets/cards/hr/welcome.draft.json").exists());
+    assert!(!out_dir.join("assets/cards/hr/details.schema.json").exists());
+    let schema: Value = serde_json::from_str(
+        &fs::read_to_string(out_dir.join("assets/cards/hr/welcome.schema.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(schema["required"][0], "fullName");
+    assert_eq!(schema["properties"]["fullName"]["type"], "string");
+    assert!(!out_dir.join("assets/cards/hr/welcome.yaml").exists());
+
+    let flow = fs::read_to_string(out_dir.join("flows/hr.ygtc")).unwrap();
+    assert!(flow.contains("assets/cards/hr/welcome.json"));
+
+    let manifest: Value = serde_json::from_str(
+        &fs::read_to_string(out_dir.join(".cards2pack/manifest.json")).unwrap(),
+    )
+    .unwrap();
+    let rel_paths: Vec<&str> = manifest["flows"][0]["cards"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|card| card["rel_path"].as_str().unwrap())
+        .collect();
+    assert_eq!(rel_paths, vec!["hr/details.jsonc", "hr/welcome.yaml"]);
+}
+
+#[test]
+fn generate_packages_template_data_and_expands_on_request() {
+    let tmp = TempDir::new().unwrap();
+    let cards_dir = tmp.path().join("cards");
+    fs::create_dir_all(&cards_dir).unwrap();
+    fs::write(
+        cards_dir.join("welcome.json"),
+        r#"{ "type": "AdaptiveCard", "body": [{ "type": "TextBlock", "text": "Hi ${name}" }], "actions": [{ "type": "Action.Submit", "data": { "step": "${next}" } }] }"#,
+    )
+    .unwrap();
+    fs::write(
+        cards_dir.join("welcome.data.yaml"),
+        "name: Ada\nnext: done\n",
+    )
+    .unwrap();
+    write_card(&cards_dir, "done.json");
+
+    let bin_dir = tmp.path().join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let greentic_pack = create_fake_greentic_pack(&bin_dir);
+
+    // The expanded run reuses the raw run's workspace, so the data asset it no longer needs must
+    // be removed.
+    let out_dir = tmp.path().join("workspace");
+    for expand in [false, true] {
+        let mut cmd = cargo_bin_cmd!("greentic-cards2pack");
+        cmd.arg("generate")
+            .arg("--cards")
+            .arg(&cards_dir)
+            .arg("--out")
+            .arg(&out_dir)
+            .arg("--name")
+            .arg("demo")
+            .arg("--greentic-pack-bin")
+            .arg(&greentic_pack);
+        if expand {
+            cmd.arg("--expand-templates");
+        }
+        cmd.assert().success();
+
+        let data_asset = out_dir.join("assets/cards/welcome.data.json");
+        if expand {
+            assert!(!data_asset.exists());
+        } else {
+            let data: Value =
+                serde_json::from_str(&fs::read_to_string(&data_asset).unwrap()).unwrap();
+            assert_eq!(data["name"], "Ada");
+        }
+        let card = fs::read_to_string(out_dir.join("assets/cards/welcome.json")).unwrap();
+        assert_eq!(card.contains("Hi Ada"), expand);
+        assert_eq!(card.contains("${name}"), !expand);
+
+        let flow = fs::read_to_string(out_dir.join("flows/misc.ygtc")).unwrap();
+        // Expanded cards route by their bound values and need no data asset at runtime.
+        assert_eq!(flow.contains("- to: done"), expand, "{flow}");
+        assert_eq!(
+            flow.contains("data_asset_path: assets/cards/welcome.data.json"),
+            !expand,
+            "{flow}"
+        );
+    }
+}
+
+#[test]
+fn generate_writes_flow_graphs_when_requested() {
+    let tmp = TempDir::new().unwrap();
+    let cards_dir = tmp.path().join("cards");
+    let out_dir = tmp.path().join("workspace");
+    fs::create_dir_all(&cards_dir).unwrap();
+    write_card(&cards_dir, "card.json");
+
+    let bin_dir = tmp.path().join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let greentic_pack = create_fake_greentic_pack(&bin_dir);
+
+    cargo_bin_cmd!("greentic-cards2pack")
+        .arg("generate")
+        .arg("--cards")
+        .arg(&cards_dir)
+        .arg("--out")
+        .arg(&out_dir)
+        .arg("--name")
+        .arg("demo")
+        .arg("--graph")
+        .arg("mermaid")
+        .arg("--greentic-pack-bin")
+        .arg(greentic_pack)
+        .assert()
+        .success();
+
+    let mermaid = fs::read_to_string(out_dir.join(".cards2pack/graphs/misc.mmd")).unwrap();
+    assert!(mermaid.contains("flowchart TD"));
+}
+
+#[test]
+fn generate_skips_unchanged_flows_and_pack_build() {
+    let tmp = TempDir::new().unwrap();
+    let cards_dir = tmp.path().join("cards");
+    let out_dir = tmp.path().join("workspace");
+    fs::create_dir_all(&cards_dir).unwrap();
+    write_card(&cards_dir, "hr/welcome.json");
+    write_card(&cards_dir, "sales/quote.json");
+
+    let bin_dir = tmp.path().join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let greentic_pack = create_fake_greentic_pack(&bin_dir);
+    let tmp_flows = out_dir.join(".cards2pack/tmp");
+    let generate = |extra: &[&str]| {
+        let output = cargo_bin_cmd!("greentic-cards2pack")
+            .arg("generate")
+            .arg("--cards")
+            .arg(&cards_dir)
+            .arg("--out")
+            .arg(&out_dir)
+            .arg("--name")
+            .arg("demo")
+            .arg("--group-by")
+            .arg("folder")
+            .arg("--greentic-pack-bin")
+            .arg(&greentic_pack)
+            .args(extra)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stderr).unwrap()
+    };
+
+    assert!(!generate(&[]).contains("No changes since the last build"));
+    assert!(out_dir.join(".cards2pack/build-state.json").is_file());
+
+    // Nothing changed: no flow is re-emitted (emission writes scratch files) and the pack is kept.
+    fs::remove_dir_all(&tmp_flows).unwrap();
+    assert!(generate(&[]).contains("No changes since the last build"));
+    assert!(!tmp_flows.join("hr.flow.yaml").exists());
+
+    // A card edit that leaves the graph alone is recopied and rebuilt without re-emitting flows.
+    fs::write(
+        cards_dir.join("hr/welcome.json"),
+        "{ \"type\": \"AdaptiveCard\", \"body\": [], \"actions\": [] }\n",
+    )
+    .unwrap();
+    assert!(!generate(&[]).contains("No changes since the last build"));
+    let asset = fs::read_to_string(out_dir.join("assets/cards/hr/welcome.json")).unwrap();
+    assert!(asset.contains("\"body\""));
+    assert!(!tmp_flows.join("hr.flow.yaml").exists());
+
+    // A hand-edited flow is re-emitted.
+    let flow_path = out_dir.join("flows/sales.ygtc");
+    let flow = fs::read_to_string(&flow_path).unwrap();
+    fs::write(&flow_path, flow.replacen("quote", "edited", 1)).unwrap();
+    generate(&[]);
+    assert!(tmp_flows.join("sales.flow.yaml").exists());
+    assert!(!tmp_flows.join("hr.flow.yaml").exists());
+
+    assert!(!generate(&["--force"]).contains("No changes since the last build"));
+    assert!(tmp_flows.join("hr.flow.yaml").exists());
+}
+
+fn snapshot_tree(root: &Path) -> Vec<(PathBuf, Vec<u8>)> {
+    let mut files: Vec<(PathBuf, Vec<u8>)> = walkdir::WalkDir::new(root)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| (entry.path().to_path_buf(), fs::read(entry.path()).unwrap()))
+        .collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn generate_dry_run_reports_changes_without_touching_out() {
+    let tmp = TempDir::new().unwrap();
+    let cards_dir = tmp.path().join("cards");
+    let out_dir = tmp.path().join("workspace");
+    fs::create_dir_all(&cards_dir).unwrap();
+    write_card(&cards_dir, "card.json");
+
+    let bin_dir = tmp.path().join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let greentic_pack = create_fake_greentic_pack(&bin_dir);
+
+    cargo_bin_cmd!("greentic-cards2pack")
+        .arg("generate")
+        .arg("--cards")
+        .arg(&cards_dir)
+        .arg("--out")
+        .arg(&out_dir)
+        .arg("--name")
+        .arg("demo")
+        .arg("--greentic-pack-bin")
+        .arg(&greentic_pack)
+        .assert()
+        .success();
+
+    write_card(&cards_dir, "extra.json");
+    let before = snapshot_tree(&out_dir);
+
+    let output = cargo_bin_cmd!("greentic-cards2pack")
+        .arg("generate")
+        .arg("--cards")
+        .arg(&cards_dir)
+        .arg("--out")
+        .arg(&out_dir)
+        .arg("--name")
+        .arg("demo")
+        .arg("--greentic-pack-bin")
+        .arg(&greentic_pack)
+        .arg("--dry-run")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("create assets/cards/extra.json"));
+    assert!(stdout.contains("modify flows/misc.ygtc"));
+    assert!(stdout.contains("--- a/flows/misc.ygtc\n+++ b/flows/misc.ygtc\n"));
+    assert!(!stdout.contains(".cards2pack/build-state.json"), "{stdout}");
+    assert_eq!(snapshot_tree(&out_dir), before);
+
+    // An unchanged tree plans no changes, although the manifest's timestamp would differ.
+    fs::remove_file(cards_dir.join("extra.json")).unwrap();
+    let output = cargo_bin_cmd!("greentic-cards2pack")
+        .arg("generate")
+        .arg("--cards")
+        .arg(&cards_dir)
+        .arg("--out")
+        .arg(&out_dir)
+        .arg("--name")
+        .arg("demo")
+        .arg("--greentic-pack-bin")
+        .arg(&greentic_pack)
+        .arg("--dry-run")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("No changes."), "{stdout}");
+}
+
+#[test]
+fn generate_dry_run_prints_json_plan() {
+    let tmp = TempDir::new().unwrap();
+    let cards_dir = tmp.path().join("cards");
+    let out_dir = tmp.path().join("workspace");
+    fs::create_dir_all(&cards_dir).unwrap();
+    write_card(&cards_dir, "card.json");
+
+    let bin_dir = tmp.path().join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let greentic_pack = create_fake_greentic_pack(&bin_dir);
+
+    let output = cargo_bin_cmd!("greentic-cards2pack")
+        .arg("generate")
+        .arg("--cards")
+        .arg(&cards_dir)
+        .arg("--out")
+        .arg(&out_dir)
+        .arg("--name")
+        .arg("demo")
+        .arg("--greentic-pack-bin")
+        .arg(&greentic_pack)
+        .arg("--dry-run")
+        .arg("--format")
+        .arg("json")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(!out_dir.exists());
+
+    let report: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        report["out"].as_str().map(PathBuf::from),
+        Some(out_dir.clone())
+    );
+    assert_eq!(
+        report["diagnostics"]["workspace_root"]
+            .as_str()
+            .map(PathBuf::from),
+        Some(out_dir)
+    );
+    let changes = report["changes"].as_array().unwrap();
+    let flow = changes
+        .iter()
+        .find(|change| change["path"] == "flows/misc.ygtc")
+        .unwrap();
+    assert_eq!(flow["kind"], "create");
+    assert!(
+        flow["diff"]
+            .as_str()
+            .unwrap()
+            .starts_with("--- /dev/null\n+++ b/flows/misc.ygtc\n")
+    );
+    assert!(report["warnings"].is_array());
+}
+
+#[test]
+fn generate_dry_run_writes_local_component_paths_relative_to_out() {
+    let tmp = TempDir::new().unwrap();
+    let cards_dir = tmp.path().join("cards");
+    let out_dir = tmp.path().join("workspace");
+    fs::create_dir_all(&cards_dir).unwrap();
+    write_card(&cards_dir, "card.json");
+    let wasm = tmp.path().join("component.wasm");
+    fs::write(&wasm, "wasm").unwrap();
+
+    let bin_dir = tmp.path().join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let greentic_pack = create_fake_greentic_pack(&bin_dir);
+
+    let generate = |dry_run: bool| {
+        let mut command = cargo_bin_cmd!("greentic-cards2pack");
+        command
+            .current_dir(tmp.path())
+            .env("GREENTIC_COMPONENT_ADAPTIVE_CARD_WASM", "component.wasm")
+            .arg("generate")
+            .arg("--cards")
+            .arg(&cards_dir)
+            .arg("--out")
+            .arg(&out_dir)
+            .arg("--name")
+            .arg("demo")
+            .arg("--greentic-pack-bin")
+            .arg(&greentic_pack);
+        if dry_run {
+            command.arg("--dry-run");
+        }
+        command.output().unwrap()
+    };
+
+    assert!(generate(false).status.success());
+    let sidecar = fs::read_to_string(out_dir.join("flows/misc.ygtc.resolve.json")).unwrap();
+    assert!(sidecar.contains("file://../../component.wasm"), "{sidecar}");
+
+    let output = generate(true);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("No changes."), "{stdout}");
+}
+
+#[test]
+fn generate_json_format_prints_full_report_on_stdout() {
+    let tmp = TempDir::new().unwrap();
+    let cards_dir = tmp.path().join("cards");
+    let out_dir = tmp.path().join("workspace");
+    fs::create_dir_all(&cards_dir).unwrap();
+    write_card(&cards_dir, "card.json");
+    for index in 0..6 {
+        fs::write(
+            cards_dir.join(format!("data-{index}.json")),
+            "{ \"foo\": 1 }",
+        )
+        .unwrap();
+    }
+
+    let bin_dir = tmp.path().join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let greentic_pack = create_fake_greentic_pack(&bin_dir);
+
+    let output = cargo_bin_cmd!("greentic-cards2pack")
+        .arg("generate")
+        .arg("--cards")
+        .arg(&cards_dir)
+        .arg("--out")
+        .arg(&out_dir)
+        .arg("--name")
+        .arg("demo")
+        .arg("--greentic-pack-bin")
+        .arg(greentic_pack)
+        .arg("--format")
+        .arg("json")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let report: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        report["manifest_path"].as_str().map(PathBuf::from),
+        Some(out_dir.join(".cards2pack/manifest.json"))
+    );
+    let warnings = report["warnings"].as_array().unwrap();
+    let ignored = warnings
+        .iter()
+        .filter(|warning| warning["kind"] == "ignored_file")
+        .collect::<Vec<_>>();
+    assert_eq!(ignored.len(), 6);
+    // Ignored files are allowed by default: listed, but not counted.
+    assert!(ignored.iter().all(|warning| warning["severity"] == "allow"));
+    let counted = warnings
+        .iter()
+        .filter(|warning| warning["severity"] != "allow")
+        .count();
+    assert_eq!(
+        report["diagnostics"]["warnings_count"].as_u64(),
+        Some(counted as u64)
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("created pack in"));
+}
@@ -0,0 +1,97 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_cards2pack::cli::GraphFormat;
+use greentic_cards2pack::graph::build_flow_graph;
+use greentic_cards2pack::graph_export::{graph_json, render_graph};
+use greentic_cards2pack::scan::{ScanConfig, scan_cards};
+use tempfile::TempDir;
+
+fn fixtures_root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/cards/flow_emit")
+}
+
+fn demo_graph(dir: &Path) -> greentic_cards2pack::graph::FlowGraph {
+    for name in [
+        "card-a.json",
+        "step-b.json",
+        "card-c.json",
+        "unresolved.json",
+    ] {
+        fs::copy(fixtures_root().join(name), dir.join(name)).unwrap();
+    }
+    let config = ScanConfig {
+        cards_dir: dir.to_path_buf(),
+        ..ScanConfig::default()
+    };
+    let manifest = scan_cards(&config).unwrap();
+    build_flow_graph(&manifest.flows[0], false).unwrap()
+}
+
+#[test]
+fn mermaid_marks_entry_stub_and_route_keys() {
+    let tmp = TempDir::new().unwrap();
+    let graph = demo_graph(tmp.path());
+
+    let mermaid = render_graph(&graph, GraphFormat::Mermaid);
+    assert!(mermaid.contains("flowchart TD"));
+    assert!(mermaid.contains("[\"CARD-C (entry)\"]):::entry"));
+    assert!(mermaid.contains("[\"missing-step (stub)\"]:::stub"));
+    assert!(mermaid.contains("-->|\"step-b\"|"));
+}
+
+#[test]
+fn dot_marks_entry_stub_and_route_keys() {
+    let tmp = TempDir::new().unwrap();
+    let graph = demo_graph(tmp.path());
+
+    let dot = render_graph(&graph, GraphFormat::Dot);
+    assert!(dot.starts_with("digraph \"demo\" {"));
+    assert!(dot.contains("\"CARD-C\" [label=\"CARD-C (entry)\", style=bold"));
+    assert!(dot.contains("\"missing-step\" [label=\"missing-step (stub)\", style=dashed]"));
+    assert!(dot.contains("\"card-a\" -> \"step-b\" [label=\"step-b\"];"));
+}
+
+#[test]
+fn json_lists_nodes_and_edges() {
+    let tmp = TempDir::new().unwrap();
+    let graph = demo_graph(tmp.path());
+
+    let json = graph_json(&graph);
+    assert_eq!(json["flow"], "demo");
+    assert_eq!(json["entry"], "CARD-C");
+    let nodes = json["nodes"].as_array().unwrap();
+    assert!(
+        nodes
+            .iter()
+            .any(|node| node["id"] == "missing-step" && node["stub"] == true)
+    );
+    let edges = json["edges"].as_array().unwrap();
+    assert!(edges.iter().any(|edge| {
+        edge["from"] == "card-a" && edge["to"] == "CARD-C" && edge["key"] == "CARD-C"
+    }));
+}
+
+#[test]
+fn graph_command_writes_one_file_per_flow() {
+    let tmp = TempDir::new().unwrap();
+    let cards = tmp.path().join("cards");
+    let out = tmp.path().join("graphs");
+    fs::create_dir_all(&cards).unwrap();
+    demo_graph(&cards);
+
+    cargo_bin_cmd!("greentic-cards2pack")
+        .arg("graph")
+        .arg("--cards")
+        .arg(&cards)
+        .arg("--format")
+        .arg("dot")
+        .arg("--out")
+        .arg(&out)
+        .assert()
+        .success();
+
+    let dot = fs::read_to_string(out.join("demo.dot")).unwrap();
+    assert!(dot.contains("digraph \"demo\""));
+}
@@ -0,0 +1,74 @@
+use std::fs;
+use std::path::PathBuf;
+
+use greentic_cards2pack::cli::GroupBy;
+use greentic_cards2pack::ir::{RouteTarget, Severity};
+use greentic_cards2pack::manifest::{
+    MANIFEST_VERSION, manifest_schema, migrate, parse_manifest, read_manifest,
+};
+use greentic_cards2pack::scan::{ScanConfig, scan_cards};
+use serde_json::json;
+
+fn repo_path(rel: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(rel)
+}
+
+/// `schema/manifest.schema.json` is the published contract; regenerate it with
+/// `UPDATE_SCHEMA=1 cargo test --test manifest`.
+#[test]
+fn published_schema_matches_the_manifest_types() {
+    let path = repo_path("schema/manifest.schema.json");
+    let generated = serde_json::to_string_pretty(&manifest_schema()).unwrap() + "\n";
+    if std::env::var_os("UPDATE_SCHEMA").is_some() {
+        fs::write(&path, &generated).unwrap();
+    }
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        generated,
+        "schema/manifest.schema.json is stale; rerun with UPDATE_SCHEMA=1"
+    );
+}
+
+#[test]
+fn reads_back_a_scanned_manifest() {
+    let config = ScanConfig {
+        cards_dir: repo_path("tests/fixtures/cards/flow_emit"),
+        ..ScanConfig::default()
+    };
+    let manifest = scan_cards(&config).unwrap();
+    assert_eq!(manifest.version, MANIFEST_VERSION);
+
+    let written = serde_json::to_string_pretty(&manifest).unwrap();
+    let read = parse_manifest(&written).unwrap();
+    assert_eq!(serde_json::to_string_pretty(&read).unwrap(), written);
+}
+
+#[test]
+fn migrates_version_1_manifests() {
+    let manifest = read_manifest(&repo_path("tests/fixtures/manifest/v1.json")).unwrap();
+    assert_eq!(manifest.version, MANIFEST_VERSION);
+
+    let card = &manifest.flows[0].cards[0];
+    assert_eq!(card.asset_path, "welcome.json");
+    assert_eq!(card.actions[0].path, "/actions/0");
+    assert!(matches!(
+        card.actions[0].target,
+        Some(RouteTarget::Step(ref step)) if step == "profile"
+    ));
+    assert_eq!(manifest.warnings[0].severity, Severity::Warn);
+    assert_eq!(manifest.diagnostics.warnings_count, 1);
+
+    let newer = migrate(json!({ "version": MANIFEST_VERSION + 1 })).unwrap_err();
+    assert!(
+        newer
+            .to_string()
+            .starts_with("unsupported manifest version")
+    );
+}
+
+#[test]
+fn migrates_version_1_group_by_variant_names() {
+    let manifest = read_manifest(&repo_path("tests/fixtures/manifest/v1_group_by.json")).unwrap();
+    assert_eq!(manifest.input.group_by, Some(GroupBy::FlowField));
+    assert_eq!(manifest.config.unwrap().group_by, Some(GroupBy::Folder));
+}
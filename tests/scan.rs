@@ -1,9 +1,12 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use greentic_cards2pack::cli::GroupBy;
-use greentic_cards2pack::ir::RouteTarget;
-use greentic_cards2pack::scan::{ScanConfig, scan_cards};
+use greentic_cards2pack::cli::{GroupBy, HostProfile};
+use greentic_cards2pack::diagnostics::StrictErrors;
+use greentic_cards2pack::graph::{build_flow_graph, build_flow_graph_collecting};
+use greentic_cards2pack::ir::{RouteTarget, Severity, WarningKind};
+use greentic_cards2pack::scan::{RoutingKeys, ScanConfig, scan_cards, scan_cards_collecting};
 use tempfile::TempDir;
 
 fn fixtures_root() -> PathBuf {
@@ -27,9 +30,8 @@ fn resolves_card_id_and_flow_from_actions() {
 
     let config = ScanConfig {
         cards_dir: tmp.path().to_path_buf(),
-        group_by: None,
-        default_flow: None,
         strict: true,
+        ..ScanConfig::default()
     };
 
     let manifest = scan_cards(&config).unwrap();
@@ -49,7 +51,7 @@ fn resolves_card_id_and_flow_from_actions() {
     assert_eq!(action.title.as_deref(), Some("Next"));
     match action.target.as_ref().unwrap() {
         RouteTarget::Step(value) => assert_eq!(value, "collect"),
-        RouteTarget::CardId(_) => panic!("expected step target"),
+        other => panic!("expected step target, got {other:?}"),
     }
 }
 
@@ -60,8 +62,8 @@ fn uses_folder_grouping_for_flow() {
     let config = ScanConfig {
         cards_dir: cards_dir.clone(),
         group_by: Some(GroupBy::Folder),
-        default_flow: None,
         strict: true,
+        ..ScanConfig::default()
     };
 
     let manifest = scan_cards(&config).unwrap();
@@ -82,9 +84,9 @@ fn uses_filename_for_card_id() {
 
     let config = ScanConfig {
         cards_dir: cards_dir.clone(),
-        group_by: None,
         default_flow: Some("ops".to_string()),
         strict: true,
+        ..ScanConfig::default()
     };
 
     let manifest = scan_cards(&config).unwrap();
@@ -105,9 +107,8 @@ fn inconsistent_cardid_strict_errors() {
 
     let config = ScanConfig {
         cards_dir: tmp.path().to_path_buf(),
-        group_by: None,
-        default_flow: None,
         strict: true,
+        ..ScanConfig::default()
     };
 
     let result = scan_cards(&config);
@@ -121,9 +122,8 @@ fn ignores_non_card_json_with_warning() {
 
     let config = ScanConfig {
         cards_dir: tmp.path().to_path_buf(),
-        group_by: None,
         default_flow: Some("ops".to_string()),
-        strict: false,
+        ..ScanConfig::default()
     };
 
     let manifest = scan_cards(&config).unwrap();
@@ -135,3 +135,824 @@ fn ignores_non_card_json_with_warning() {
     );
     assert!(manifest.flows.is_empty());
 }
+
+fn write_card(dir: &Path, rel: &str, contents: &str) {
+    let path = dir.join(rel);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+#[test]
+fn flow_field_grouping_uses_pointer_and_reports_missing() {
+    let tmp = TempDir::new().unwrap();
+    write_card(
+        tmp.path(),
+        "a.json",
+        r#"{"type":"AdaptiveCard","meta":{"journey":"onboarding"},"actions":[{"type":"Action.Submit","data":{"flow":"legacy"}}]}"#,
+    );
+    write_card(
+        tmp.path(),
+        "b.json",
+        r#"{"type":"AdaptiveCard","greentic":{"flow":"support"},"body":[]}"#,
+    );
+
+    let config = ScanConfig {
+        cards_dir: tmp.path().to_path_buf(),
+        group_by: Some(GroupBy::FlowField),
+        flow_field: Some("/meta/journey".to_string()),
+        ..ScanConfig::default()
+    };
+
+    let manifest = scan_cards(&config).unwrap();
+    let flows: Vec<&str> = manifest
+        .flows
+        .iter()
+        .map(|flow| flow.flow_name.as_str())
+        .collect();
+    assert_eq!(flows, vec!["onboarding", "support"]);
+    assert_eq!(manifest.input.flow_field.as_deref(), Some("/meta/journey"));
+    assert!(manifest.warnings.iter().any(|w| {
+        w.message.contains("a.json") && w.message.contains("disagrees with flow field")
+    }));
+    assert!(manifest.warnings.iter().any(|w| {
+        w.message
+            .contains("b.json has no flow field /meta/journey; using support")
+    }));
+}
+
+#[test]
+fn flow_field_grouping_strict_rejects_mixed_strategies() {
+    let tmp = TempDir::new().unwrap();
+    write_card(
+        tmp.path(),
+        "a.json",
+        r#"{"type":"AdaptiveCard","greentic":{"flow":"ops"},"body":[]}"#,
+    );
+    write_card(tmp.path(), "b.json", r#"{"type":"AdaptiveCard","body":[]}"#);
+
+    let config = ScanConfig {
+        cards_dir: tmp.path().to_path_buf(),
+        group_by: Some(GroupBy::FlowField),
+        default_flow: Some("ops".to_string()),
+        strict: true,
+        ..ScanConfig::default()
+    };
+
+    let err = scan_cards(&config).unwrap_err().to_string();
+    assert!(
+        err.contains("b.json has no flow field /greentic/flow"),
+        "{err}"
+    );
+    assert!(err.contains("--default-flow"), "{err}");
+}
+
+#[test]
+fn folder_grouping_only_places_cards_without_a_flow() {
+    let tmp = TempDir::new().unwrap();
+    write_card(
+        tmp.path(),
+        "hr/welcome.json",
+        r#"{"type":"AdaptiveCard","greentic":{"flow":"it"},"body":[]}"#,
+    );
+    write_card(
+        tmp.path(),
+        "hr/goodbye.json",
+        r#"{"type":"AdaptiveCard","greentic":{"cardId":"goodbye"},"body":[]}"#,
+    );
+
+    let config = ScanConfig {
+        cards_dir: tmp.path().to_path_buf(),
+        group_by: Some(GroupBy::Folder),
+        ..ScanConfig::default()
+    };
+
+    let manifest = scan_cards(&config).unwrap();
+    let flows: Vec<(&str, &str)> = manifest
+        .flows
+        .iter()
+        .flat_map(|flow| {
+            flow.cards
+                .iter()
+                .map(|card| (card.rel_path.as_str(), flow.flow_name.as_str()))
+        })
+        .collect();
+    assert_eq!(
+        flows,
+        vec![("hr/goodbye.json", "hr"), ("hr/welcome.json", "it")]
+    );
+    assert!(manifest.warnings.is_empty());
+}
+
+#[test]
+fn folder_grouping_strict_rejects_mixed_strategies() {
+    let tmp = TempDir::new().unwrap();
+    write_card(
+        tmp.path(),
+        "hr/welcome.json",
+        r#"{"type":"AdaptiveCard","greentic":{"flow":"it"},"body":[]}"#,
+    );
+    write_card(
+        tmp.path(),
+        "hr/goodbye.json",
+        r#"{"type":"AdaptiveCard","greentic":{"cardId":"goodbye"},"body":[]}"#,
+    );
+    write_card(
+        tmp.path(),
+        "top.json",
+        r#"{"type":"AdaptiveCard","greentic":{"flow":"hr"},"body":[]}"#,
+    );
+
+    let config = ScanConfig {
+        cards_dir: tmp.path().to_path_buf(),
+        group_by: Some(GroupBy::Folder),
+        strict: true,
+        ..ScanConfig::default()
+    };
+
+    let err = scan_cards(&config).unwrap_err().to_string();
+    assert!(
+        err.contains("card flow it in hr/welcome.json disagrees with folder grouping (hr)"),
+        "{err}"
+    );
+    assert!(
+        err.contains("top.json has no folder; refusing to mix grouping strategies (card flow)"),
+        "{err}"
+    );
+    assert!(!err.contains("goodbye"), "{err}");
+}
+
+#[test]
+fn normalises_flow_names_that_are_not_one_path_segment() {
+    let tmp = TempDir::new().unwrap();
+    write_card(
+        tmp.path(),
+        "escape.json",
+        r#"{"type":"AdaptiveCard","greentic":{"flow":"../../x"},"body":[]}"#,
+    );
+    write_card(
+        tmp.path(),
+        "nested.json",
+        r#"{"type":"AdaptiveCard","actions":[
+            {"type":"Action.Submit","title":"Go","data":{"flow":"a/b","step":"next"}}
+        ]}"#,
+    );
+
+    let mut config = ScanConfig {
+        cards_dir: tmp.path().to_path_buf(),
+        ..ScanConfig::default()
+    };
+
+    let manifest = scan_cards(&config).unwrap();
+    let flows: Vec<&str> = manifest
+        .flows
+        .iter()
+        .map(|flow| flow.flow_name.as_str())
+        .collect();
+    assert_eq!(flows, vec!["a-b", "x"]);
+    let warning = manifest
+        .warnings
+        .iter()
+        .find(|w| w.rel_path.as_deref() == Some("escape.json"))
+        .unwrap();
+    assert_eq!(warning.kind, WarningKind::Inconsistent);
+    assert_eq!(warning.flow.as_deref(), Some("x"));
+
+    config.strict = true;
+    let err = scan_cards(&config).unwrap_err().to_string();
+    assert!(
+        err.contains("flow name \"../../x\" in escape.json"),
+        "{err}"
+    );
+    assert!(err.contains("flow name \"a/b\" in nested.json"), "{err}");
+
+    config.strict = false;
+    config.default_flow = Some("../ops".to_string());
+    assert!(scan_cards(&config).is_err());
+}
+
+#[test]
+fn rejects_flow_field_that_is_not_a_pointer() {
+    let tmp = TempDir::new().unwrap();
+    let config = ScanConfig {
+        cards_dir: tmp.path().to_path_buf(),
+        group_by: Some(GroupBy::FlowField),
+        flow_field: Some("greentic.flow".to_string()),
+        ..ScanConfig::default()
+    };
+
+    assert!(scan_cards(&config).is_err());
+}
+
+#[test]
+fn honours_custom_routing_keys() {
+    let tmp = TempDir::new().unwrap();
+    write_card(
+        tmp.path(),
+        "start.json",
+        r#"{"type":"AdaptiveCard","meta":{"id":"start","journey":"hr"},"actions":[
+            {"type":"Action.Submit","title":"Go","data":{"next":"details"}},
+            {"type":"Action.Submit","title":"Help","data":{"action":{"target":"help"}}},
+            {"type":"Action.Submit","title":"Ignored","data":{"step":"legacy"}}
+        ]}"#,
+    );
+
+    let config = ScanConfig {
+        cards_dir: tmp.path().to_path_buf(),
+        strict: true,
+        routing_keys: RoutingKeys {
+            card_id: vec!["/meta/id".to_string()],
+            flow: vec!["/meta/journey".to_string()],
+            target: vec!["/data/next".to_string(), "/data/action/target".to_string()],
+            ..RoutingKeys::default()
+        },
+        ..ScanConfig::default()
+    };
+
+    let manifest = scan_cards(&config).unwrap();
+    let card = &manifest.flows[0].cards[0];
+    assert_eq!(manifest.flows[0].flow_name, "hr");
+    assert_eq!(card.card_id, "start");
+    let targets: Vec<Option<&str>> = card
+        .actions
+        .iter()
+        .map(|action| match action.target.as_ref() {
+            Some(RouteTarget::Step(value)) => Some(value.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(targets, vec![Some("details"), Some("help"), None]);
+    assert!(manifest.input.routing_keys.is_some());
+}
+
+#[test]
+fn discovers_nested_actions_with_paths() {
+    let cards_dir = fixtures_root().join("nested_actions");
+
+    let config = ScanConfig {
+        cards_dir,
+        strict: true,
+        ..ScanConfig::default()
+    };
+
+    let manifest = scan_cards(&config).unwrap();
+    let hub = manifest.flows[0]
+        .cards
+        .iter()
+        .find(|card| card.card_id == "hub")
+        .unwrap();
+    let found: Vec<(&str, &str)> = hub
+        .actions
+        .iter()
+        .map(|action| (action.path.as_str(), action.action_type.as_str()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("/actions/0", "Action.ShowCard"),
+            ("/actions/0/card/actions/0", "Action.Submit"),
+            ("/body/0/items/0/actions/0", "Action.Submit"),
+            ("/body/0/items/0/actions/1", "Action.ToggleVisibility"),
+            ("/body/0/selectAction", "Action.Submit"),
+            ("/body/1/columns/0/selectAction", "Action.Submit"),
+        ]
+    );
+
+    let graph = build_flow_graph(&manifest.flows[0], true).unwrap();
+    let edges: Vec<(&str, &str)> = graph.nodes["hub"]
+        .routes
+        .iter()
+        .map(|route| (route.key.as_str(), route.target.as_str()))
+        .collect();
+    assert_eq!(
+        edges,
+        vec![
+            ("exit", "exit"),
+            ("help", "help"),
+            ("details", "details"),
+            ("details-2", "details"),
+        ]
+    );
+}
+
+#[test]
+fn pairs_templates_with_data_and_reports_unbound_variables() {
+    let cards_dir = fixtures_root().join("templated");
+
+    let config = ScanConfig {
+        cards_dir: cards_dir.clone(),
+        ..ScanConfig::default()
+    };
+
+    let manifest = scan_cards(&config).unwrap();
+    assert_eq!(manifest.diagnostics.cards_processed, 1);
+    let card = &manifest.flows[0].cards[0];
+    assert_eq!(card.data_path.as_deref(), Some("welcome.data.json"));
+    assert_eq!(card.data_asset_path.as_deref(), Some("welcome.data.json"));
+
+    let unbound: Vec<&str> = manifest
+        .warnings
+        .iter()
+        .filter(|w| w.kind == WarningKind::UnboundVariable)
+        .map(|w| w.message.as_str())
+        .collect();
+    assert_eq!(unbound.len(), 1);
+    assert!(unbound[0].ends_with(": employee.manager"), "{}", unbound[0]);
+    assert!(
+        manifest.warnings.iter().any(|w| {
+            w.kind == WarningKind::IgnoredFile && w.message.contains("orphan.data.json")
+        })
+    );
+
+    let strict = ScanConfig {
+        strict: true,
+        ..config
+    };
+    assert!(scan_cards(&strict).is_err());
+}
+
+#[test]
+fn parallel_scan_matches_sequential_scan() {
+    let config = ScanConfig {
+        cards_dir: fixtures_root(),
+        jobs: Some(1),
+        ..ScanConfig::default()
+    };
+    let sequential = scan_cards(&config).unwrap();
+    let parallel = scan_cards(&ScanConfig {
+        jobs: Some(8),
+        ..config
+    })
+    .unwrap();
+
+    assert_eq!(
+        serde_json::to_value(&sequential.flows).unwrap(),
+        serde_json::to_value(&parallel.flows).unwrap()
+    );
+    assert_eq!(
+        serde_json::to_value(&sequential.warnings).unwrap(),
+        serde_json::to_value(&parallel.warnings).unwrap()
+    );
+    assert!(!sequential.warnings.is_empty());
+}
+
+#[test]
+fn checks_cards_against_host_profile() {
+    let config = ScanConfig {
+        cards_dir: fixtures_root().join("execute_verbs"),
+        host: Some(HostProfile::Webex),
+        ..ScanConfig::default()
+    };
+
+    let manifest = scan_cards(&config).unwrap();
+    let compatibility: Vec<&str> = manifest
+        .warnings
+        .iter()
+        .filter(|w| w.kind == WarningKind::Compatibility)
+        .map(|w| w.message.as_str())
+        .collect();
+    assert!(
+        compatibility.contains(&"request.json declares version 1.4 but webex supports up to 1.3")
+    );
+    assert!(compatibility.contains(
+        &"Action.Execute at /actions/0 in request.json requires version 1.4 (webex supports up to 1.3)"
+    ));
+    assert_eq!(manifest.input.host, Some(HostProfile::Webex));
+
+    let teams = scan_cards(&ScanConfig {
+        host: Some(HostProfile::Teams),
+        ..config.clone()
+    })
+    .unwrap();
+    assert!(
+        teams
+            .warnings
+            .iter()
+            .all(|w| w.kind != WarningKind::Compatibility)
+    );
+
+    let strict = ScanConfig {
+        strict: true,
+        ..config
+    };
+    let err = scan_cards(&strict).unwrap_err().to_string();
+    assert!(err.contains("webex supports up to 1.3"), "{err}");
+}
+
+#[test]
+fn checks_expanded_templates_for_compatibility() {
+    let tmp = TempDir::new().unwrap();
+    write_card(
+        tmp.path(),
+        "welcome.json",
+        r#"{"type":"AdaptiveCard","version":"${version}","body":[{"type":"${kind}","columns":[]}]}"#,
+    );
+    write_card(
+        tmp.path(),
+        "welcome.data.json",
+        r#"{"version":"1.2","kind":"Table"}"#,
+    );
+    let compatibility = |expand_templates| {
+        let config = ScanConfig {
+            cards_dir: tmp.path().to_path_buf(),
+            expand_templates,
+            ..ScanConfig::default()
+        };
+        scan_cards(&config)
+            .unwrap()
+            .warnings
+            .into_iter()
+            .filter(|w| w.kind == WarningKind::Compatibility)
+            .map(|w| w.message)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        compatibility(false),
+        vec!["welcome.json declares unknown Adaptive Card version \"${version}\""]
+    );
+    assert_eq!(
+        compatibility(true),
+        vec!["Table at /body/0 in welcome.json requires version 1.5 (card declares 1.2)"]
+    );
+}
+
+#[test]
+fn groups_localized_variants_into_one_node() {
+    let config = ScanConfig {
+        cards_dir: fixtures_root().join("localized"),
+        locale_suffixes: true,
+        ..ScanConfig::default()
+    };
+
+    let manifest = scan_cards(&config).unwrap();
+    assert!(
+        manifest
+            .warnings
+            .iter()
+            .all(|w| w.kind != WarningKind::DuplicateCardId)
+    );
+    let flow = &manifest.flows[0];
+    assert_eq!(flow.flow_name, "onboarding");
+    let mut variants: Vec<(&str, Option<&str>)> = flow
+        .cards
+        .iter()
+        .map(|card| (card.card_id.as_str(), card.locale.as_deref()))
+        .collect();
+    variants.sort();
+    assert_eq!(
+        variants,
+        vec![
+            ("done", Some("de")),
+            ("done", Some("en")),
+            ("welcome", Some("de")),
+            ("welcome", Some("en")),
+            ("welcome", Some("fr")),
+        ]
+    );
+
+    let graph = build_flow_graph(flow, false).unwrap();
+    assert_eq!(graph.nodes.len(), 2);
+    let welcome = &graph.nodes["welcome"];
+    assert_eq!(
+        welcome.card_path.as_deref(),
+        Some("assets/cards/welcome.de.json")
+    );
+    assert_eq!(
+        welcome.locales.keys().collect::<Vec<_>>(),
+        vec!["de", "en", "fr"]
+    );
+    assert_eq!(welcome.locales["en"], "assets/cards/welcome.en.json");
+    let targets: Vec<&str> = welcome
+        .routes
+        .iter()
+        .map(|route| route.target.as_str())
+        .collect();
+    assert_eq!(targets, vec!["done"]);
+
+    let messages: Vec<(WarningKind, &str)> = graph
+        .warnings
+        .iter()
+        .map(|w| (w.kind, w.message.as_str()))
+        .collect();
+    assert!(messages.contains(&(
+        WarningKind::Inconsistent,
+        "localized variant welcome.fr.json of card welcome in flow onboarding has different actions than welcome.de.json"
+    )));
+    assert!(messages.contains(&(
+        WarningKind::MissingLocale,
+        "card done in flow onboarding has no fr variant"
+    )));
+    assert!(build_flow_graph(flow, true).is_err());
+}
+
+#[test]
+fn locale_suffixes_are_opt_in_language_codes() {
+    let tmp = TempDir::new().unwrap();
+    let card = r#"{"type":"AdaptiveCard","greentic":{"flow":"main"},"body":[]}"#;
+    for name in [
+        "approve.json",
+        "approve.ok.json",
+        "x.id.json",
+        "x.no.json",
+        "welcome.de.json",
+    ] {
+        write_card(tmp.path(), name, card);
+    }
+    let ids = |locale_suffixes| {
+        let manifest = scan_cards(&ScanConfig {
+            cards_dir: tmp.path().to_path_buf(),
+            locale_suffixes,
+            ..ScanConfig::default()
+        })
+        .unwrap();
+        manifest.flows[0]
+            .cards
+            .iter()
+            .map(|card| (card.card_id.clone(), card.locale.clone()))
+            .collect::<Vec<_>>()
+    };
+
+    let owned = |id: &str, locale: Option<&str>| (id.to_string(), locale.map(str::to_string));
+    assert_eq!(
+        ids(false),
+        vec![
+            owned("approve", None),
+            owned("approve.ok", None),
+            owned("welcome.de", None),
+            owned("x.id", None),
+            owned("x.no", None),
+        ]
+    );
+    // `ok` is not a language code.
+    assert_eq!(
+        ids(true),
+        vec![
+            owned("approve", None),
+            owned("approve.ok", None),
+            owned("welcome", Some("de")),
+            owned("x", Some("id")),
+            owned("x", Some("no")),
+        ]
+    );
+}
+
+#[test]
+fn locale_suffix_colliding_with_another_card_is_reported() {
+    let tmp = TempDir::new().unwrap();
+    write_card(
+        tmp.path(),
+        "cart.json",
+        r#"{"type":"AdaptiveCard","greentic":{"cardId":"checkout","flow":"main"},"body":[]}"#,
+    );
+    write_card(
+        tmp.path(),
+        "checkout.de.json",
+        r#"{"type":"AdaptiveCard","greentic":{"flow":"main"},"body":[]}"#,
+    );
+    write_card(
+        tmp.path(),
+        "done.json",
+        r#"{"type":"AdaptiveCard","greentic":{"flow":"main"},"body":[]}"#,
+    );
+    write_card(
+        tmp.path(),
+        "done.de.json",
+        r#"{"type":"AdaptiveCard","greentic":{"flow":"main"},"body":[]}"#,
+    );
+
+    let config = ScanConfig {
+        cards_dir: tmp.path().to_path_buf(),
+        locale_suffixes: true,
+        ..ScanConfig::default()
+    };
+    let manifest = scan_cards(&config).unwrap();
+    let messages: Vec<(WarningKind, &str)> = manifest
+        .warnings
+        .iter()
+        .map(|w| (w.kind, w.message.as_str()))
+        .collect();
+    assert_eq!(
+        messages,
+        vec![(
+            WarningKind::Inconsistent,
+            "checkout.de.json is locale de of card checkout by its file name, but cart.json also defines card checkout; give one of them its own cardId"
+        )]
+    );
+}
+
+#[test]
+fn strict_mode_reports_every_error_grouped_by_file() {
+    let tmp = TempDir::new().unwrap();
+    write_card(
+        tmp.path(),
+        "a.json",
+        r#"{"type":"AdaptiveCard","actions":[{"type":"Action.Submit","data":{"cardId":"x"}},{"type":"Action.Submit","data":{"cardId":"y"}}]}"#,
+    );
+    write_card(
+        tmp.path(),
+        "b.json",
+        r#"{"type":"AdaptiveCard","greentic":{"cardId":"b","flow":"misc"},"actions":[{"type":"Action.Submit","data":{"step":"nowhere"}}]}"#,
+    );
+    write_card(tmp.path(), "broken.json", r#"{"type":"#);
+
+    let config = ScanConfig {
+        cards_dir: tmp.path().to_path_buf(),
+        strict: true,
+        ..ScanConfig::default()
+    };
+
+    let mut errors = StrictErrors::new(config.max_errors);
+    let manifest = scan_cards_collecting(&config, &mut errors).unwrap();
+    for flow in &manifest.flows {
+        build_flow_graph_collecting(flow, config.policy(), &mut errors).unwrap();
+    }
+    let report = errors.finish().unwrap_err().to_string();
+    assert!(
+        report.starts_with("5 findings denied in 3 files:\na.json:\n"),
+        "{report}"
+    );
+    assert!(
+        report.contains(
+            "a.json:\n  - 1:82: [inconsistent] inconsistent cardId values in a.json: x, y\n  - 1:1: [missing_flow] unable to resolve flow name for a.json\n  - 1:82: [missing_target] missing target y referenced from card x in flow misc\n"
+        ),
+        "{report}"
+    );
+    assert!(
+        report.contains("\nbroken.json:\n  - 1:8: [invalid_json] invalid "),
+        "{report}"
+    );
+    assert!(
+        report
+            .ends_with("\nb.json:\n  - 1:75: [missing_target] missing target nowhere referenced from card b in flow misc"),
+        "{report}"
+    );
+
+    let limited = ScanConfig {
+        max_errors: Some(2),
+        ..config
+    };
+    let report = scan_cards(&limited).unwrap_err().to_string();
+    assert!(
+        report.starts_with("2 findings denied in 1 file (stopped at --max-errors 2):"),
+        "{report}"
+    );
+}
+
+#[test]
+fn warnings_carry_source_locations() {
+    let tmp = TempDir::new().unwrap();
+    write_card(
+        tmp.path(),
+        "a.json",
+        r#"{
+  "type": "AdaptiveCard",
+  "greentic": { "flow": "main" }, "body": [{ "type": "Input.Text" }],
+  "actions": [
+    { "type": "Action.Submit", "data": { "cardId": "a" } },
+    { "type": "Action.Submit", "data": { "cardId": "b" } },
+    { "type": "Action.Submit", "data": { "step": "nowhere" } }
+  ]
+}"#,
+    );
+
+    let config = ScanConfig {
+        cards_dir: tmp.path().to_path_buf(),
+        ..ScanConfig::default()
+    };
+
+    let manifest = scan_cards(&config).unwrap();
+    let file = tmp.path().join("a.json").display().to_string();
+    let inconsistent = manifest
+        .warnings
+        .iter()
+        .find(|w| w.kind == WarningKind::Inconsistent)
+        .unwrap();
+    let location = inconsistent.location.as_ref().unwrap();
+    assert_eq!(location.file, file);
+    assert_eq!((location.line, location.column), (Some(6), Some(5)));
+    assert_eq!(location.pointer.as_deref(), Some("/actions/1"));
+    assert_eq!(
+        inconsistent.to_string(),
+        format!("{file}:6:5: inconsistent cardId values in a.json: a, b")
+    );
+
+    let graph = build_flow_graph(&manifest.flows[0], false).unwrap();
+    let missing = graph
+        .warnings
+        .iter()
+        .find(|w| w.kind == WarningKind::MissingTarget && w.message.contains("nowhere"))
+        .unwrap();
+    assert_eq!(
+        missing.location.as_ref().unwrap().to_string(),
+        format!("{file}:7:5")
+    );
+    assert_eq!(missing.card_id.as_deref(), Some("a"));
+    assert_eq!(missing.flow.as_deref(), Some("main"));
+    assert_eq!(missing.rel_path.as_deref(), Some("a.json"));
+    assert_eq!(missing.target.as_deref(), Some("nowhere"));
+    assert_eq!(missing.route_key, None);
+    assert_eq!(inconsistent.rel_path.as_deref(), Some("a.json"));
+
+    // Per-card findings raised before the card id and flow were resolved carry them too.
+    let unnamed_input = manifest
+        .warnings
+        .iter()
+        .find(|w| w.message.contains("without id"))
+        .unwrap();
+    for finding in [inconsistent, unnamed_input] {
+        assert_eq!(finding.card_id.as_deref(), Some("a"), "{finding}");
+        assert_eq!(finding.flow.as_deref(), Some("main"), "{finding}");
+    }
+}
+
+#[test]
+fn severity_overrides_deny_allow_and_relax_strict() {
+    let tmp = TempDir::new().unwrap();
+    write_card(
+        tmp.path(),
+        "a.json",
+        r#"{"type":"AdaptiveCard","greentic":{"flow":"main","cardId":"a"},"actions":[{"type":"Action.Submit","data":{"step":"nowhere"}}]}"#,
+    );
+    write_card(tmp.path(), "list.json", "[1, 2]");
+
+    let config = ScanConfig {
+        cards_dir: tmp.path().to_path_buf(),
+        severities: BTreeMap::from([(WarningKind::IgnoredFile, Severity::Allow)]),
+        ..ScanConfig::default()
+    };
+    let manifest = scan_cards(&config).unwrap();
+    assert_eq!(manifest.warnings.len(), 1);
+    assert_eq!(manifest.warnings[0].severity, Severity::Allow);
+    assert_eq!(manifest.diagnostics.warnings_count, 0);
+    let graph = build_flow_graph_collecting(
+        &manifest.flows[0],
+        config.policy(),
+        &mut StrictErrors::default(),
+    )
+    .unwrap();
+    assert_eq!(graph.warnings[0].kind, WarningKind::MissingTarget);
+    assert_eq!(graph.warnings[0].severity, Severity::Warn);
+
+    // Denying a kind fails the run without --strict; warning or allowing one exempts it
+    // from --strict.
+    let deny = ScanConfig {
+        severities: BTreeMap::from([
+            (WarningKind::IgnoredFile, Severity::Allow),
+            (WarningKind::MissingTarget, Severity::Deny),
+        ]),
+        ..config.clone()
+    };
+    let mut errors = StrictErrors::default();
+    build_flow_graph_collecting(&manifest.flows[0], deny.policy(), &mut errors).unwrap();
+    let report = errors.finish().unwrap_err().to_string();
+    assert!(
+        report.contains("missing target nowhere referenced from card a in flow main"),
+        "{report}"
+    );
+
+    let strict = ScanConfig {
+        strict: true,
+        severities: BTreeMap::from([(WarningKind::MissingTarget, Severity::Warn)]),
+        ..config
+    };
+    let mut errors = StrictErrors::default();
+    let graph =
+        build_flow_graph_collecting(&manifest.flows[0], strict.policy(), &mut errors).unwrap();
+    assert!(errors.is_empty());
+    assert!(graph.nodes["nowhere"].stub);
+}
+
+#[test]
+fn warns_on_invalid_json_and_duplicate_card_ids_unless_denied() {
+    let tmp = TempDir::new().unwrap();
+    let card = r#"{"type":"AdaptiveCard","greentic":{"flow":"main","cardId":"a"},"actions":[]}"#;
+    write_card(tmp.path(), "a.json", card);
+    write_card(tmp.path(), "b.json", card);
+    write_card(tmp.path(), "broken.json", r#"{"type":"#);
+
+    let config = ScanConfig {
+        cards_dir: tmp.path().to_path_buf(),
+        ..ScanConfig::default()
+    };
+    let manifest = scan_cards(&config).unwrap();
+    assert_eq!(manifest.flows[0].cards.len(), 1);
+    let kinds: Vec<WarningKind> = manifest.warnings.iter().map(|w| w.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![WarningKind::InvalidJson, WarningKind::DuplicateCardId]
+    );
+
+    let denied = ScanConfig {
+        severities: BTreeMap::from([
+            (WarningKind::DuplicateCardId, Severity::Deny),
+            (WarningKind::InvalidJson, Severity::Deny),
+        ]),
+        ..config
+    };
+    let report = scan_cards(&denied).unwrap_err().to_string();
+    assert!(
+        report.contains("[duplicate_card_id] duplicate card_id a"),
+        "{report}"
+    );
+    assert!(report.contains("[invalid_json] invalid "), "{report}");
+}
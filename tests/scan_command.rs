@@ -0,0 +1,270 @@
+use std::fs;
+use std::path::PathBuf;
+
+use assert_cmd::cargo::cargo_bin_cmd;
+use serde_json::Value;
+use tempfile::TempDir;
+
+fn fixtures_root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/cards/flow_emit")
+}
+
+#[test]
+fn scan_prints_manifest_json_without_greentic_tools() {
+    let output = cargo_bin_cmd!("greentic-cards2pack")
+        .arg("scan")
+        .arg("--cards")
+        .arg(fixtures_root())
+        .arg("--format")
+        .arg("json")
+        .env("GREENTIC_PACK_BIN", "/nonexistent/greentic-pack")
+        .env("PATH", "")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let manifest: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let flows = manifest["flows"].as_array().unwrap();
+    assert_eq!(flows.len(), 1);
+    assert_eq!(flows[0]["flow_name"], "demo");
+    let warnings = manifest["warnings"].as_array().unwrap();
+    assert!(warnings.iter().any(|warning| {
+        warning["kind"] == "missing_target"
+            && warning["message"]
+                .as_str()
+                .is_some_and(|message| message.contains("missing-step"))
+    }));
+    assert_eq!(
+        manifest["diagnostics"]["warnings_count"].as_u64(),
+        Some(warnings.len() as u64)
+    );
+}
+
+#[test]
+fn scan_prints_text_summary() {
+    let tmp = TempDir::new().unwrap();
+    fs::copy(
+        fixtures_root().join("card-a.json"),
+        tmp.path().join("card-a.json"),
+    )
+    .unwrap();
+    fs::copy(
+        fixtures_root().join("step-b.json"),
+        tmp.path().join("step-b.json"),
+    )
+    .unwrap();
+    fs::copy(
+        fixtures_root().join("card-c.json"),
+        tmp.path().join("card-c.json"),
+    )
+    .unwrap();
+
+    let output = cargo_bin_cmd!("greentic-cards2pack")
+        .arg("scan")
+        .arg("--cards")
+        .arg(tmp.path())
+        .arg("--strict")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("- demo (3 cards)"));
+    assert!(stdout.contains("- card-a (card-a.json)"));
+    assert!(stdout.contains("Action.Submit \"Go B\" -> step step-b"));
+    assert!(stdout.contains("Warnings: 0"));
+}
+
+#[test]
+fn scan_strict_fails_on_missing_target() {
+    cargo_bin_cmd!("greentic-cards2pack")
+        .arg("scan")
+        .arg("--cards")
+        .arg(fixtures_root())
+        .arg("--strict")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn scan_honours_ignore_file_and_globs() {
+    let tmp = TempDir::new().unwrap();
+    let card = r#"{ "type": "AdaptiveCard", "body": [] }"#;
+    for rel in [
+        "hr/welcome.json",
+        "hr/old-welcome.json",
+        "drafts/idea.json",
+        "samples/payload.json",
+        "sales/offer.yaml",
+    ] {
+        let path = tmp.path().join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, card).unwrap();
+    }
+    fs::write(
+        tmp.path().join(".cards2packignore"),
+        "# not cards\ndrafts/\n/samples\n",
+    )
+    .unwrap();
+
+    let output = cargo_bin_cmd!("greentic-cards2pack")
+        .arg("scan")
+        .arg("--cards")
+        .arg(tmp.path())
+        .arg("--default-flow")
+        .arg("main")
+        .arg("--include")
+        .arg("hr/**")
+        .arg("--include")
+        .arg("drafts/**")
+        .arg("--exclude")
+        .arg("old-*")
+        .arg("--format")
+        .arg("json")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let manifest: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let rel_paths: Vec<&str> = manifest["flows"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .flat_map(|flow| flow["cards"].as_array().unwrap())
+        .map(|card| card["rel_path"].as_str().unwrap())
+        .collect();
+    assert_eq!(rel_paths, vec!["hr/welcome.json"]);
+    assert!(
+        manifest["warnings"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|warning| warning["kind"] != "ignored_file")
+    );
+}
+
+#[test]
+fn scan_writes_sarif_log() {
+    let tmp = TempDir::new().unwrap();
+    let sarif = tmp.path().join("reports/cards2pack.sarif");
+    let output = cargo_bin_cmd!("greentic-cards2pack")
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .arg("scan")
+        .arg("--cards")
+        .arg(fixtures_root())
+        .arg("--sarif")
+        .arg(&sarif)
+        .env("PATH", "")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let log: Value = serde_json::from_str(&fs::read_to_string(&sarif).unwrap()).unwrap();
+    assert_eq!(log["version"], "2.1.0");
+    let run = &log["runs"][0];
+    assert_eq!(run["tool"]["driver"]["name"], "greentic-cards2pack");
+    let missing = run["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|result| {
+            result["message"]["text"]
+                .as_str()
+                .is_some_and(|text| text.contains("missing-step"))
+        })
+        .unwrap();
+    assert_eq!(missing["ruleId"], "C2P002");
+    assert_eq!(missing["level"], "warning");
+    let uri = missing["locations"][0]["physicalLocation"]["artifactLocation"]["uri"]
+        .as_str()
+        .unwrap();
+    assert!(uri.starts_with("tests/fixtures/cards/flow_emit/"), "{uri}");
+}
+
+#[test]
+fn strict_scan_writes_denied_findings_to_sarif_before_failing() {
+    let tmp = TempDir::new().unwrap();
+    let sarif = tmp.path().join("cards2pack.sarif");
+    let output = cargo_bin_cmd!("greentic-cards2pack")
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .arg("scan")
+        .arg("--cards")
+        .arg(fixtures_root())
+        .arg("--strict")
+        .arg("--sarif")
+        .arg(&sarif)
+        .env("PATH", "")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("denied in"), "{stderr}");
+
+    let log: Value = serde_json::from_str(&fs::read_to_string(&sarif).unwrap()).unwrap();
+    let missing = log["runs"][0]["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|result| result["ruleId"] == "C2P002")
+        .unwrap();
+    assert_eq!(missing["level"], "error");
+    assert!(
+        missing["message"]["text"]
+            .as_str()
+            .unwrap()
+            .contains("missing-step")
+    );
+    assert!(missing["locations"][0]["physicalLocation"]["region"]["startLine"].is_u64());
+}
+
+#[test]
+fn sarif_keeps_warnings_when_max_errors_stops_the_run() {
+    let tmp = TempDir::new().unwrap();
+    let cards = tmp.path().join("cards");
+    fs::create_dir_all(&cards).unwrap();
+    for (name, step) in [("a.json", "nowhere"), ("b.json", "nowhere-else")] {
+        fs::write(
+            cards.join(name),
+            format!(
+                r#"{{"type":"AdaptiveCard","greentic":{{"flow":"main"}},"actions":[{{"type":"Action.Submit","data":{{"step":"{step}"}}}}]}}"#
+            ),
+        )
+        .unwrap();
+    }
+    fs::write(cards.join("notes.json"), r#"{"foo":1}"#).unwrap();
+    let sarif = tmp.path().join("cards2pack.sarif");
+
+    let output = cargo_bin_cmd!("greentic-cards2pack")
+        .arg("scan")
+        .arg("--cards")
+        .arg(&cards)
+        .arg("--deny")
+        .arg("missing_target")
+        .arg("--max-errors")
+        .arg("1")
+        .arg("--sarif")
+        .arg(&sarif)
+        .env("PATH", "")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("1 finding denied in 1 file (stopped at --max-errors 1):"),
+        "{stderr}"
+    );
+
+    let log: Value = serde_json::from_str(&fs::read_to_string(&sarif).unwrap()).unwrap();
+    let results: Vec<(&str, &str)> = log["runs"][0]["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|result| {
+            (
+                result["ruleId"].as_str().unwrap(),
+                result["level"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(results, vec![("C2P007", "note"), ("C2P002", "error")]);
+}
@@ -1,3 +1,5 @@
+#![allow(dead_code)]
+
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -214,3 +216,41 @@ esac
         path
     }
 }
+
+/// Writes a `greentic-flow` stub into `dir` that creates the flow file on `new` and appends a node
+/// on `add-step`, and returns a `PATH` that finds it first. Enough for `generate` to run without
+/// the real tool; the emitted flows are not valid.
+#[cfg(unix)]
+pub fn install_stub_greentic_flow(dir: &Path) -> std::ffi::OsString {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = dir.join("greentic-flow");
+    fs::write(
+        &path,
+        r#"#!/bin/sh
+cmd="$1"
+shift
+flow=""
+node=""
+while [ $# -gt 0 ]; do
+  case "$1" in
+    --flow) flow="$2"; shift 2 ;;
+    --node-id) node="$2"; shift 2 ;;
+    *) shift ;;
+  esac
+done
+case "$cmd" in
+  new) printf 'nodes:\n' > "$flow" ;;
+  add-step) printf '  %s: {}\n' "$node" >> "$flow" ;;
+esac
+"#,
+    )
+    .unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let mut dirs = vec![dir.to_path_buf()];
+    dirs.extend(std::env::split_paths(
+        &std::env::var_os("PATH").unwrap_or_default(),
+    ));
+    std::env::join_paths(dirs).unwrap()
+}